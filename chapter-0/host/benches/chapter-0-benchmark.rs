@@ -3,14 +3,16 @@ extern crate criterion;
 use criterion::Criterion;
 
 extern crate chapter_0;
-use chapter_0::image::Image;
+use chapter_0::generate;
+use chapter_0::image::{Image, Pixel};
+use chapter_0::planar::PlanarImage;
 
 fn parallel_bench(criterion: &mut Criterion) {
     use chapter_0::filter::bilateral_parallel as filter;
 
-    let input_512 = Image::open("../../fixtures/input-512.png").unwrap();
-    let input_1024 = Image::open("../../fixtures/input-1024.png").unwrap();
-    let input_2048 = Image::open("../../fixtures/input-2048.png").unwrap();
+    let input_512: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let input_1024: Image = Image::open("../../fixtures/input-1024.png").unwrap();
+    let input_2048: Image = generate::photographic(2048, 2048, 42);
 
     criterion
         .sample_size(20)
@@ -37,9 +39,9 @@ fn parallel_bench(criterion: &mut Criterion) {
 fn sequential_bench(criterion: &mut Criterion) {
     use chapter_0::filter::bilateral_sequential as filter;
 
-    let input_512 = Image::open("../../fixtures/input-512.png").unwrap();
-    let input_1024 = Image::open("../../fixtures/input-1024.png").unwrap();
-    let input_2048 = Image::open("../../fixtures/input-2048.png").unwrap();
+    let input_512: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let input_1024: Image = Image::open("../../fixtures/input-1024.png").unwrap();
+    let input_2048: Image = generate::photographic(2048, 2048, 42);
 
     criterion
         .sample_size(20)
@@ -63,5 +65,78 @@ fn sequential_bench(criterion: &mut Criterion) {
         });
 }
 
-criterion_group!(benches, parallel_bench, sequential_bench);
+// Planar filters with and without the conversion, against the interleaved ones above.
+fn planar_bench(criterion: &mut Criterion) {
+    use chapter_0::filter::{bilateral_parallel_planar, bilateral_sequential_planar, Options};
+
+    let input_512: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let input_1024: Image = Image::open("../../fixtures/input-1024.png").unwrap();
+
+    let planar_512 = PlanarImage::from_image(&input_512);
+    let planar_1024 = PlanarImage::from_image(&input_1024);
+    let options = Options::default();
+
+    criterion
+        .sample_size(20)
+        .without_plots()
+        .bench_function("sequential-planar-512", |b| {
+            b.iter(|| bilateral_sequential_planar(&planar_512, 5, 3.5, 3.0, options))
+        });
+
+    criterion
+        .sample_size(20)
+        .without_plots()
+        .bench_function("parallel-planar-512", |b| {
+            b.iter(|| bilateral_parallel_planar(&planar_512, 5, 3.5, 3.0, options))
+        });
+
+    criterion
+        .sample_size(20)
+        .without_plots()
+        .bench_function("parallel-planar-1024", |b| {
+            b.iter(|| bilateral_parallel_planar(&planar_1024, 5, 3.5, 3.0, options))
+        });
+
+    criterion.sample_size(20).without_plots().bench_function(
+        "parallel-planar-converted-1024",
+        |b| {
+            b.iter(|| {
+                let planar = PlanarImage::from_image(&input_1024);
+                bilateral_parallel_planar(&planar, 5, 3.5, 3.0, options).to_image::<Pixel>()
+            })
+        },
+    );
+}
+
+// Table driven filter, against the parallel one it is derived from.
+fn fast_bench(criterion: &mut Criterion) {
+    use chapter_0::filter::bilateral_fast as filter;
+
+    let input_512: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let input_1024: Image = Image::open("../../fixtures/input-1024.png").unwrap();
+    let input_2048: Image = generate::photographic(2048, 2048, 42);
+
+    criterion
+        .sample_size(20)
+        .without_plots()
+        .bench_function("fast-512", |b| b.iter(|| filter(&input_512, 5, 3.5, 3.0)));
+
+    criterion
+        .sample_size(20)
+        .without_plots()
+        .bench_function("fast-1024", |b| b.iter(|| filter(&input_1024, 5, 3.5, 3.0)));
+
+    criterion
+        .sample_size(20)
+        .without_plots()
+        .bench_function("fast-2048", |b| b.iter(|| filter(&input_2048, 5, 3.5, 3.0)));
+}
+
+criterion_group!(
+    benches,
+    parallel_bench,
+    sequential_bench,
+    planar_bench,
+    fast_bench
+);
 criterion_main!(benches);
@@ -0,0 +1,173 @@
+use image::{Pixel, Sample};
+
+// Reference white of sRGB (D65), as the sums of the rows of the RGB to XYZ matrix.
+const WHITE_X: f64 = 0.950_470;
+const WHITE_Y: f64 = 1.0;
+const WHITE_Z: f64 = 1.088_830;
+
+// Break point of the CIELAB companding function, `6 / 29`.
+const LAB_DELTA: f64 = 6.0 / 29.0;
+
+/// Color space the `bilateral_*_in_space` filters measure range distances in.
+///
+/// Only those RGB filters of this crate take one: the other CPU filters, and the CUDA ones of
+/// the later chapters, always measure distances between samples as they are stored.
+///
+/// Pixels are only converted to weigh their neighbours, the output stays in the color space
+/// of the input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorSpace {
+    /// Samples as they are stored, gamma encoded sRGB for most files.
+    Rgb,
+    /// sRGB decoded to linear light, scaled back to the range of the samples.
+    LinearRgb,
+    /// CIELAB relative to D65, so distances are CIE76 color differences (ΔE).
+    ///
+    /// Lightness goes from 0 to 100 whatever the sample type is.
+    Lab,
+    /// Full range BT.601 luma and chroma, as in JPEG, scaled to the range of the samples.
+    YCbCr,
+}
+
+impl ColorSpace {
+    /// Coordinates of `pixel` in the color space.
+    pub fn coordinates<T: Sample>(self, pixel: &Pixel<T>) -> [f64; 3] {
+        let rgb = [pixel.r.to_f64(), pixel.g.to_f64(), pixel.b.to_f64()];
+
+        match self {
+            ColorSpace::Rgb => rgb,
+            ColorSpace::LinearRgb => {
+                scale(map(scale(rgb, 1.0 / T::WHITE), srgb_to_linear), T::WHITE)
+            }
+            ColorSpace::Lab => rgb_to_lab(scale(rgb, 1.0 / T::WHITE)),
+            ColorSpace::YCbCr => scale(rgb_to_ycbcr(scale(rgb, 1.0 / T::WHITE)), T::WHITE),
+        }
+    }
+
+    /// Pixel at `coordinates` of the color space, the inverse of `coordinates`.
+    ///
+    /// Integer samples are rounded to the nearest value, so that they survive a round trip.
+    pub fn pixel<T: Sample>(self, coordinates: [f64; 3]) -> Pixel<T> {
+        let rgb = match self {
+            ColorSpace::Rgb => coordinates,
+            ColorSpace::LinearRgb => scale(
+                map(scale(coordinates, 1.0 / T::WHITE), linear_to_srgb),
+                T::WHITE,
+            ),
+            ColorSpace::Lab => scale(lab_to_rgb(coordinates), T::WHITE),
+            ColorSpace::YCbCr => scale(ycbcr_to_rgb(scale(coordinates, 1.0 / T::WHITE)), T::WHITE),
+        };
+
+        let sample = |value: f64| T::from_f64(if T::INTEGER { value.round() } else { value });
+
+        Pixel {
+            r: sample(rgb[0]),
+            g: sample(rgb[1]),
+            b: sample(rgb[2]),
+        }
+    }
+}
+
+/// Decodes a gamma encoded sRGB value in `[0, 1]` into linear light.
+pub fn srgb_to_linear(value: f64) -> f64 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes linear light in `[0, 1]` into a gamma encoded sRGB value.
+pub fn linear_to_srgb(value: f64) -> f64 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts gamma encoded sRGB in `[0, 1]` into CIELAB `[L, a, b]`.
+pub fn rgb_to_lab(rgb: [f64; 3]) -> [f64; 3] {
+    let linear = map(rgb, srgb_to_linear);
+    let (r, g, b) = (linear[0], linear[1], linear[2]);
+
+    let x = 0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b;
+    let y = 0.212_672_9 * r + 0.715_152_2 * g + 0.072_175_0 * b;
+    let z = 0.019_333_9 * r + 0.119_192_0 * g + 0.950_304_1 * b;
+
+    let (fx, fy, fz) = (
+        lab_compand(x / WHITE_X),
+        lab_compand(y / WHITE_Y),
+        lab_compand(z / WHITE_Z),
+    );
+
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// Converts CIELAB `[L, a, b]` into gamma encoded sRGB in `[0, 1]`.
+///
+/// Colors outside of the sRGB gamut come out of `[0, 1]`.
+pub fn lab_to_rgb(lab: [f64; 3]) -> [f64; 3] {
+    let fy = (lab[0] + 16.0) / 116.0;
+    let fx = fy + lab[1] / 500.0;
+    let fz = fy - lab[2] / 200.0;
+
+    let (x, y, z) = (
+        lab_expand(fx) * WHITE_X,
+        lab_expand(fy) * WHITE_Y,
+        lab_expand(fz) * WHITE_Z,
+    );
+
+    let r = 3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z;
+    let g = -0.969_266_0 * x + 1.876_010_8 * y + 0.041_556_0 * z;
+    let b = 0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z;
+
+    map([r, g, b], linear_to_srgb)
+}
+
+/// Converts RGB in `[0, 1]` into full range BT.601 `[Y, Cb, Cr]`, chroma centered on 0.5.
+pub fn rgb_to_ycbcr(rgb: [f64; 3]) -> [f64; 3] {
+    let (r, g, b) = (rgb[0], rgb[1], rgb[2]);
+    let y = 0.299 * r + 0.587 * g + 0.114 * b;
+
+    [y, 0.5 + (b - y) / 1.772, 0.5 + (r - y) / 1.402]
+}
+
+/// Converts full range BT.601 `[Y, Cb, Cr]` into RGB in `[0, 1]`.
+pub fn ycbcr_to_rgb(ycbcr: [f64; 3]) -> [f64; 3] {
+    let (y, cb, cr) = (ycbcr[0], ycbcr[1], ycbcr[2]);
+
+    let r = y + 1.402 * (cr - 0.5);
+    let b = y + 1.772 * (cb - 0.5);
+    let g = (y - 0.299 * r - 0.114 * b) / 0.587;
+
+    [r, g, b]
+}
+
+fn lab_compand(t: f64) -> f64 {
+    if t > LAB_DELTA * LAB_DELTA * LAB_DELTA {
+        t.cbrt()
+    } else {
+        t / (3.0 * LAB_DELTA * LAB_DELTA) + 4.0 / 29.0
+    }
+}
+
+fn lab_expand(f: f64) -> f64 {
+    if f > LAB_DELTA {
+        f * f * f
+    } else {
+        3.0 * LAB_DELTA * LAB_DELTA * (f - 4.0 / 29.0)
+    }
+}
+
+fn map<F: Fn(f64) -> f64>(values: [f64; 3], function: F) -> [f64; 3] {
+    [
+        function(values[0]),
+        function(values[1]),
+        function(values[2]),
+    ]
+}
+
+fn scale(values: [f64; 3], factor: f64) -> [f64; 3] {
+    map(values, |value| value * factor)
+}
@@ -0,0 +1,70 @@
+use std::cmp::min;
+use std::io::{Read, Write};
+
+use image::{Image, ImageError, PngPixel, RowReader, RowWriter};
+
+/// Filters an image streamed row by row, holding only a band of rows in memory.
+///
+/// Bands of `band_height` rows are filtered together with up to `radius` rows above and below
+/// them, which is all a filter of that radius looks at. The first and last bands end where
+/// the image does, so the output is identical to filtering the whole image at once with every
+/// `BorderMode` but `Wrap`, which would need the rows at the other end of the image.
+///
+/// `filter` also gets the image row the band starts at. Filters dithering their output pass it
+/// to `Options::starting_at_row`, so that the threshold matrix lines up across bands.
+pub fn filter_bands<P, R, W, F>(
+    reader: &mut RowReader<P, R>,
+    writer: &mut RowWriter<P, W>,
+    band_height: usize,
+    radius: usize,
+    filter: F,
+) -> Result<(), ImageError>
+where
+    P: PngPixel,
+    R: Read,
+    W: Write,
+    F: Fn(&Image<P>, usize) -> Image<P>,
+{
+    assert!(band_height > 0, "bands have to be at least a row high");
+    assert_eq!((reader.width, reader.height), (writer.width, writer.height));
+
+    let (width, height) = (reader.width, reader.height);
+
+    // Rows read so far and still needed, the first of them being `first_row` of the image.
+    let mut rows: Vec<Vec<P>> = Vec::new();
+    let mut first_row = 0;
+    let mut start = 0;
+
+    while start < height {
+        let end = min(start + band_height, height);
+        let halo_start = start.saturating_sub(radius);
+        let halo_end = min(end + radius, height);
+
+        rows.drain(..halo_start - first_row);
+        first_row = halo_start;
+
+        while first_row + rows.len() < halo_end {
+            let mut row = vec![P::default(); width];
+
+            reader.read_row(&mut row)?;
+            rows.push(row);
+        }
+
+        let mut band = Image::new(width, halo_end - halo_start);
+        band.metadata = reader.metadata.clone();
+
+        for (index, row) in rows.iter().enumerate() {
+            band.pixels[index * width..(index + 1) * width].clone_from_slice(row);
+        }
+
+        let output = filter(&band, halo_start);
+
+        for i in start - halo_start..end - halo_start {
+            writer.write_row(&output.pixels[i * width..(i + 1) * width])?;
+        }
+
+        start = end;
+    }
+
+    Ok(())
+}
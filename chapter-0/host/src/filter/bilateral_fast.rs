@@ -0,0 +1,155 @@
+use rayon::prelude::*;
+
+use filter::kernel::{Interleaved, Kernel};
+use filter::{check_views, Options};
+use image::{GenericPixel, Image, Pixel, Sample};
+use view::{ImageView, ImageViewMut};
+
+// Entries of the range table per `2 * sigma_r^2` of color distance.
+const RANGE_STEPS: usize = 256;
+
+// Distance, in `2 * sigma_r^2`, past which range weights are taken as zero.
+const RANGE_EXTENT: usize = 32;
+
+/// Like the parallel filter, but spatial weights come from a table of every offset in the
+/// window and range weights are interpolated in a table of the exponent.
+///
+/// Results stay within `max_deviation(radius)` of those of `bilateral_sequential`, before
+/// they are quantized to samples.
+pub fn filter<T: Sample + Send + Sync>(
+    source: &Image<Pixel<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+) -> Image<Pixel<T>> {
+    filter_with(source, radius, sigma_d, sigma_r, Options::default())
+}
+
+/// Filters any pixel format with every behaviour set through `options`.
+pub fn filter_with<P: GenericPixel + Send + Sync>(
+    source: &Image<P>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) -> Image<P> {
+    let mut destination = Image::new(source.width, source.height);
+    destination.metadata = source.metadata.clone();
+
+    filter_with_view(
+        &source.as_view(),
+        &mut destination.as_view_mut(),
+        radius,
+        sigma_d,
+        sigma_r,
+        options,
+    );
+
+    destination
+}
+
+/// Borders are those of the image the views are taken from, not of the region.
+pub fn filter_with_view<P: GenericPixel + Send + Sync>(
+    source: &ImageView<P>,
+    destination: &mut ImageViewMut<P>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    check_views(source, destination);
+
+    let width = source.width();
+    let kernel = Kernel {
+        radius,
+        sigma_d,
+        sigma_r,
+    };
+    let neighbours = Interleaved::new(source, &options);
+    let tables = Tables::new(radius, sigma_d, sigma_r);
+
+    let pixels: Vec<_> = (0..source.height() * width)
+        .into_par_iter()
+        .map(|index| {
+            let (y, x) = (source.y() + index / width, source.x() + index % width);
+
+            neighbours.filter_pixel_weighted(&kernel, y, x, &options, |dk, dl, distance| {
+                tables.spatial(dk, dl) * tables.range(distance)
+            })
+        })
+        .collect();
+
+    for (index, pixel) in pixels.into_iter().enumerate() {
+        *destination.pixel_mut(index / width, index % width) = pixel;
+    }
+}
+
+/// Largest difference from `bilateral_sequential` of a filtered value before quantization,
+/// as a fraction of white.
+///
+/// The bound assumes every sample lies between zero and white, as integer samples always do.
+/// Float samples past white, like HDR ones, can be off by more.
+///
+/// Interpolating `exp(-x)` linearly every `1 / 256` is off by less than `1 / 524288`, and
+/// every weight of the window can be off by that much: `0.06` of an 8-bit step at radius 5.
+/// Quantized samples can still end up one step apart, when the exact value is close to a
+/// step.
+pub fn max_deviation(radius: usize) -> f64 {
+    let side = (2 * radius + 1) as f64;
+    let steps = RANGE_STEPS as f64;
+
+    side * side * (1.0 / (8.0 * steps * steps) + (-(RANGE_EXTENT as f64)).exp())
+}
+
+struct Tables {
+    // Weights of the `(2r + 1)^2` offsets, row by row, centered on the middle entry.
+    spatial: Vec<f64>,
+    side: usize,
+    // `exp(-x)` at every `1 / RANGE_STEPS`.
+    range: Vec<f64>,
+    range_scale: f64,
+}
+
+impl Tables {
+    fn new(radius: usize, sigma_d: f64, sigma_r: f64) -> Self {
+        let side = 2 * radius + 1;
+        let mut spatial = Vec::with_capacity(side * side);
+
+        for k in 0..side {
+            for l in 0..side {
+                let (dk, dl) = (k as f64 - radius as f64, l as f64 - radius as f64);
+                spatial.push(f64::exp(-(dk * dk + dl * dl) / (2.0 * sigma_d * sigma_d)));
+            }
+        }
+
+        let range = (0..RANGE_STEPS * RANGE_EXTENT + 2)
+            .map(|index| f64::exp(-(index as f64) / RANGE_STEPS as f64))
+            .collect();
+
+        Tables {
+            spatial,
+            side,
+            range,
+            range_scale: RANGE_STEPS as f64 / (2.0 * sigma_r * sigma_r),
+        }
+    }
+
+    fn spatial(&self, dk: i32, dl: i32) -> f64 {
+        let center = (self.side / 2) as i32;
+
+        self.spatial[(center + dk) as usize * self.side + (center + dl) as usize]
+    }
+
+    fn range(&self, distance: f64) -> f64 {
+        let position = distance * self.range_scale;
+        let index = position as usize;
+
+        if index + 1 >= self.range.len() {
+            return 0.0;
+        }
+
+        let fraction = position - index as f64;
+
+        self.range[index] + (self.range[index + 1] - self.range[index]) * fraction
+    }
+}
@@ -1,79 +1,268 @@
 use rayon::prelude::*;
-use std::cmp::{max, min};
+use color::ColorSpace;
+use filter::kernel::{InSpace, Interleaved, Kernel, Planar};
+use filter::{check_views, AlphaMode, Options};
+use image::{GenericPixel, Image, Luma, Pixel, Rgba, Sample};
+use planar::PlanarImage;
+use view::{ImageView, ImageViewMut};
 
-use image::{Image, Pixel};
+pub fn filter<T: Sample + Send + Sync>(
+    source: &Image<Pixel<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+) -> Image<Pixel<T>> {
+    let mut destination = Image::new(source.width, source.height);
+    destination.metadata = source.metadata.clone();
+
+    filter_view(
+        &source.as_view(),
+        &mut destination.as_view_mut(),
+        radius,
+        sigma_d,
+        sigma_r,
+    );
 
-pub fn filter(source: &Image, radius: usize, sigma_d: f64, sigma_r: f64) -> Image {
+    destination
+}
+
+/// Like `filter`, but measures range distances between pixels in `space`.
+///
+/// `sigma_r` is in the units of that space, e.g. ΔE for `ColorSpace::Lab`. `options.alpha`
+/// doesn't apply, as the pixels have no alpha.
+pub fn filter_in_space<T: Sample + Send + Sync>(
+    source: &Image<Pixel<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    space: ColorSpace,
+    options: Options,
+) -> Image<Pixel<T>> {
     let mut destination = Image::new(source.width, source.height);
+    destination.metadata = source.metadata.clone();
+
+    // Every pixel is converted once, instead of once per window it falls into.
+    let guide: Vec<_> = source
+        .pixels
+        .par_iter()
+        .map(|pixel| space.coordinates(pixel))
+        .collect();
+
+    let kernel = Kernel {
+        radius,
+        sigma_d,
+        sigma_r,
+    };
+    let neighbours = InSpace::new(source, &guide, space, &options);
 
     destination.pixels = (0..source.height * source.width)
         .into_par_iter()
         .map(|index| {
-            let i = index / source.width;
-            let j = index % source.width;
+            let (i, j) = (index / source.width, index % source.width);
 
-            filter_pixel(source, radius, i, j, sigma_d, sigma_r)
+            neighbours.filter_pixel(&kernel, i, j, &options)
         })
         .collect();
 
     destination
 }
 
-fn filter_pixel(
-    source: &Image,
+pub fn filter_rgba<T: Sample + Send + Sync>(
+    source: &Image<Rgba<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    alpha: AlphaMode,
+) -> Image<Rgba<T>> {
+    let mut destination = Image::new(source.width, source.height);
+    destination.metadata = source.metadata.clone();
+
+    filter_rgba_view(
+        &source.as_view(),
+        &mut destination.as_view_mut(),
+        radius,
+        sigma_d,
+        sigma_r,
+        alpha,
+    );
+
+    destination
+}
+
+pub fn filter_luma<T: Sample + Send + Sync>(
+    source: &Image<Luma<T>>,
     radius: usize,
-    i: usize,
-    j: usize,
     sigma_d: f64,
     sigma_r: f64,
-) -> Pixel {
-    let mut r_value = 0f64;
-    let mut r_accum = 0f64;
+) -> Image<Luma<T>> {
+    let mut destination = Image::new(source.width, source.height);
+    destination.metadata = source.metadata.clone();
 
-    let mut g_value = 0f64;
-    let mut g_accum = 0f64;
+    filter_luma_view(
+        &source.as_view(),
+        &mut destination.as_view_mut(),
+        radius,
+        sigma_d,
+        sigma_r,
+    );
 
-    let mut b_value = 0f64;
-    let mut b_accum = 0f64;
+    destination
+}
 
-    for k in max(i as i32 - radius as i32, 0)..min(i as i32 + radius as i32, source.height as i32) {
-        for l in
-            max(j as i32 - radius as i32, 0)..min(j as i32 + radius as i32, source.width as i32)
-        {
-            let w = w_kernel(i as i32, j as i32, k, l, source, sigma_d, sigma_r);
+/// Filters every plane with the weights of the interleaved filters. The range distance takes
+/// all planes but the fourth of 4, which is alpha like in `Rgba` images. `options.alpha`
+/// doesn't apply: alpha planes are always filtered.
+pub fn filter_planar<T: Sample + Send + Sync>(
+    source: &PlanarImage<T>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) -> PlanarImage<T> {
+    let mut destination = PlanarImage::new(source.width, source.height, source.channels);
+    destination.metadata = source.metadata.clone();
 
-            r_value = r_value + w * source.pixels[k as usize * source.width + l as usize].r as f64;
-            r_accum = r_accum + w;
+    let size = source.width * source.height;
+    let kernel = Kernel {
+        radius,
+        sigma_d,
+        sigma_r,
+    };
+    let neighbours = Planar::new(source, &options);
+
+    // Rows hold a span of every plane, one after another.
+    let rows: Vec<Vec<T>> = (0..source.height)
+        .into_par_iter()
+        .map(|i| {
+            let mut row = vec![T::default(); source.width * source.channels];
 
-            g_value = g_value + w * source.pixels[k as usize * source.width + l as usize].g as f64;
-            g_accum = g_accum + w;
+            for j in 0..source.width {
+                let means = kernel.filter(&neighbours, i, j, &options);
+
+                for (channel, mean) in means.iter().take(source.channels).enumerate() {
+                    row[channel * source.width + j] = options.quantize(*mean, i as i32, j as i32);
+                }
+            }
+
+            row
+        })
+        .collect();
 
-            b_value = b_value + w * source.pixels[k as usize * source.width + l as usize].b as f64;
-            b_accum = b_accum + w;
+    for (i, row) in rows.iter().enumerate() {
+        for (channel, span) in row.chunks(source.width).enumerate() {
+            let start = channel * size + i * source.width;
+            destination.samples[start..start + source.width].copy_from_slice(span);
         }
     }
 
-    Pixel {
-        r: (r_value / r_accum) as u8,
-        g: (g_value / g_accum) as u8,
-        b: (b_value / b_accum) as u8,
-    }
+    destination
 }
 
-fn w_kernel(i: i32, j: i32, k: i32, l: i32, source: &Image, sigma_d: f64, sigma_r: f64) -> f64 {
-    let w_d = ((i - k) * (i - k) + (j - l) * (j - l)) as f64;
-    let w_r = l2_distance(
-        &source.pixels[i as usize * source.width + j as usize],
-        &source.pixels[k as usize * source.width + l as usize],
+/// Filters the region of `source` into `destination`, which has to be of the same size.
+///
+/// Pixels around the region are taken into account, but only the region is written.
+pub fn filter_view<T: Sample + Send + Sync>(
+    source: &ImageView<Pixel<T>>,
+    destination: &mut ImageViewMut<Pixel<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+) {
+    filter_with_view(
+        source,
+        destination,
+        radius,
+        sigma_d,
+        sigma_r,
+        Options::default(),
     );
+}
+
+pub fn filter_rgba_view<T: Sample + Send + Sync>(
+    source: &ImageView<Rgba<T>>,
+    destination: &mut ImageViewMut<Rgba<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    alpha: AlphaMode,
+) {
+    let options = Options {
+        alpha,
+        ..Options::default()
+    };
 
-    f64::exp(-w_d / (2.0 * sigma_d * sigma_d) - w_r / (2.0 * sigma_r * sigma_r))
+    filter_with_view(source, destination, radius, sigma_d, sigma_r, options);
 }
 
-fn l2_distance(lhs: &Pixel, rhs: &Pixel) -> f64 {
-    let square = (lhs.r as f64 - rhs.r as f64) * (lhs.r as f64 - rhs.r as f64)
-        + (lhs.g as f64 - rhs.g as f64) * (lhs.g as f64 - rhs.g as f64)
-        + (lhs.b as f64 - rhs.b as f64) * (lhs.b as f64 - rhs.b as f64);
+pub fn filter_luma_view<T: Sample + Send + Sync>(
+    source: &ImageView<Luma<T>>,
+    destination: &mut ImageViewMut<Luma<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+) {
+    filter_with_view(
+        source,
+        destination,
+        radius,
+        sigma_d,
+        sigma_r,
+        Options::default(),
+    );
+}
 
-    square.sqrt()
+/// Filters any pixel format with every behaviour set through `options`.
+pub fn filter_with<P: GenericPixel + Send + Sync>(
+    source: &Image<P>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) -> Image<P> {
+    let mut destination = Image::new(source.width, source.height);
+    destination.metadata = source.metadata.clone();
+
+    filter_with_view(
+        &source.as_view(),
+        &mut destination.as_view_mut(),
+        radius,
+        sigma_d,
+        sigma_r,
+        options,
+    );
+
+    destination
+}
+
+/// Borders are those of the image the views are taken from, not of the region.
+pub fn filter_with_view<P: GenericPixel + Send + Sync>(
+    source: &ImageView<P>,
+    destination: &mut ImageViewMut<P>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    check_views(source, destination);
+
+    let width = source.width();
+    let kernel = Kernel {
+        radius,
+        sigma_d,
+        sigma_r,
+    };
+    let neighbours = Interleaved::new(source, &options);
+
+    let pixels: Vec<_> = (0..source.height() * width)
+        .into_par_iter()
+        .map(|index| {
+            let (y, x) = (source.y() + index / width, source.x() + index % width);
+
+            neighbours.filter_pixel(&kernel, y, x, &options)
+        })
+        .collect();
+
+    for (index, pixel) in pixels.into_iter().enumerate() {
+        *destination.pixel_mut(index / width, index % width) = pixel;
+    }
 }
@@ -1,75 +1,245 @@
-use std::cmp::{max, min};
+use color::ColorSpace;
+use filter::kernel::{InSpace, Interleaved, Kernel, Planar};
+use filter::{check_views, AlphaMode, Options};
+use image::{GenericPixel, Image, Luma, Pixel, Rgba, Sample};
+use planar::PlanarImage;
+use view::{ImageView, ImageViewMut};
+
+pub fn filter<T: Sample>(
+    source: &Image<Pixel<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+) -> Image<Pixel<T>> {
+    let mut destination = Image::new(source.width, source.height);
+    destination.metadata = source.metadata.clone();
+
+    filter_view(
+        &source.as_view(),
+        &mut destination.as_view_mut(),
+        radius,
+        sigma_d,
+        sigma_r,
+    );
 
-use image::{Image, Pixel};
+    destination
+}
 
-pub fn filter(source: &Image, radius: usize, sigma_d: f64, sigma_r: f64) -> Image {
+/// Like `filter`, but measures range distances between pixels in `space`.
+///
+/// `sigma_r` is in the units of that space, e.g. ΔE for `ColorSpace::Lab`. `options.alpha`
+/// doesn't apply, as the pixels have no alpha.
+pub fn filter_in_space<T: Sample>(
+    source: &Image<Pixel<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    space: ColorSpace,
+    options: Options,
+) -> Image<Pixel<T>> {
     let mut destination = Image::new(source.width, source.height);
+    destination.metadata = source.metadata.clone();
+
+    // Every pixel is converted once, instead of once per window it falls into.
+    let guide: Vec<_> = source
+        .pixels
+        .iter()
+        .map(|pixel| space.coordinates(pixel))
+        .collect();
+
+    let kernel = Kernel {
+        radius,
+        sigma_d,
+        sigma_r,
+    };
+    let neighbours = InSpace::new(source, &guide, space, &options);
 
     for i in 0..source.height {
         for j in 0..source.width {
             destination.pixels[i * source.width + j] =
-                filter_pixel(source, radius, i, j, sigma_d, sigma_r);
+                neighbours.filter_pixel(&kernel, i, j, &options);
         }
     }
 
     destination
 }
 
-fn filter_pixel(
-    source: &Image,
+pub fn filter_rgba<T: Sample>(
+    source: &Image<Rgba<T>>,
     radius: usize,
-    i: usize,
-    j: usize,
     sigma_d: f64,
     sigma_r: f64,
-) -> Pixel {
-    let mut r_value = 0f64;
-    let mut r_accum = 0f64;
+    alpha: AlphaMode,
+) -> Image<Rgba<T>> {
+    let mut destination = Image::new(source.width, source.height);
+    destination.metadata = source.metadata.clone();
+
+    filter_rgba_view(
+        &source.as_view(),
+        &mut destination.as_view_mut(),
+        radius,
+        sigma_d,
+        sigma_r,
+        alpha,
+    );
 
-    let mut g_value = 0f64;
-    let mut g_accum = 0f64;
+    destination
+}
 
-    let mut b_value = 0f64;
-    let mut b_accum = 0f64;
+pub fn filter_luma<T: Sample>(
+    source: &Image<Luma<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+) -> Image<Luma<T>> {
+    let mut destination = Image::new(source.width, source.height);
+    destination.metadata = source.metadata.clone();
+
+    filter_luma_view(
+        &source.as_view(),
+        &mut destination.as_view_mut(),
+        radius,
+        sigma_d,
+        sigma_r,
+    );
 
-    for k in max(i as i32 - radius as i32, 0)..min(i as i32 + radius as i32, source.height as i32) {
-        for l in
-            max(j as i32 - radius as i32, 0)..min(j as i32 + radius as i32, source.width as i32)
-        {
-            let w = w_kernel(i as i32, j as i32, k, l, source, sigma_d, sigma_r);
+    destination
+}
 
-            r_value = r_value + w * source.pixels[k as usize * source.width + l as usize].r as f64;
-            r_accum = r_accum + w;
+/// Filters every plane with the weights of the interleaved filters. The range distance takes
+/// all planes but the fourth of 4, which is alpha like in `Rgba` images. `options.alpha`
+/// doesn't apply: alpha planes are always filtered.
+pub fn filter_planar<T: Sample>(
+    source: &PlanarImage<T>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) -> PlanarImage<T> {
+    let mut destination = PlanarImage::new(source.width, source.height, source.channels);
+    destination.metadata = source.metadata.clone();
+
+    let size = source.width * source.height;
+    let kernel = Kernel {
+        radius,
+        sigma_d,
+        sigma_r,
+    };
+    let neighbours = Planar::new(source, &options);
 
-            g_value = g_value + w * source.pixels[k as usize * source.width + l as usize].g as f64;
-            g_accum = g_accum + w;
+    for i in 0..source.height {
+        for j in 0..source.width {
+            let means = kernel.filter(&neighbours, i, j, &options);
 
-            b_value = b_value + w * source.pixels[k as usize * source.width + l as usize].b as f64;
-            b_accum = b_accum + w;
+            for (channel, mean) in means.iter().take(source.channels).enumerate() {
+                destination.samples[channel * size + i * source.width + j] =
+                    options.quantize(*mean, i as i32, j as i32);
+            }
         }
     }
 
-    Pixel {
-        r: (r_value / r_accum) as u8,
-        g: (g_value / g_accum) as u8,
-        b: (b_value / b_accum) as u8,
-    }
+    destination
 }
 
-fn w_kernel(i: i32, j: i32, k: i32, l: i32, source: &Image, sigma_d: f64, sigma_r: f64) -> f64 {
-    let w_d = ((i - k) * (i - k) + (j - l) * (j - l)) as f64;
-    let w_r = l2_distance(
-        &source.pixels[i as usize * source.width + j as usize],
-        &source.pixels[k as usize * source.width + l as usize],
+/// Filters the region of `source` into `destination`, which has to be of the same size.
+///
+/// Pixels around the region are taken into account, but only the region is written.
+pub fn filter_view<T: Sample>(
+    source: &ImageView<Pixel<T>>,
+    destination: &mut ImageViewMut<Pixel<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+) {
+    filter_with_view(
+        source,
+        destination,
+        radius,
+        sigma_d,
+        sigma_r,
+        Options::default(),
     );
+}
 
-    f64::exp(-w_d / (2.0 * sigma_d * sigma_d) - w_r / (2.0 * sigma_r * sigma_r))
+pub fn filter_rgba_view<T: Sample>(
+    source: &ImageView<Rgba<T>>,
+    destination: &mut ImageViewMut<Rgba<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    alpha: AlphaMode,
+) {
+    let options = Options {
+        alpha,
+        ..Options::default()
+    };
+
+    filter_with_view(source, destination, radius, sigma_d, sigma_r, options);
 }
 
-fn l2_distance(lhs: &Pixel, rhs: &Pixel) -> f64 {
-    let square = (lhs.r as f64 - rhs.r as f64) * (lhs.r as f64 - rhs.r as f64)
-        + (lhs.g as f64 - rhs.g as f64) * (lhs.g as f64 - rhs.g as f64)
-        + (lhs.b as f64 - rhs.b as f64) * (lhs.b as f64 - rhs.b as f64);
+pub fn filter_luma_view<T: Sample>(
+    source: &ImageView<Luma<T>>,
+    destination: &mut ImageViewMut<Luma<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+) {
+    filter_with_view(
+        source,
+        destination,
+        radius,
+        sigma_d,
+        sigma_r,
+        Options::default(),
+    );
+}
 
-    square.sqrt()
+/// Filters any pixel format with every behaviour set through `options`.
+pub fn filter_with<P: GenericPixel>(
+    source: &Image<P>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) -> Image<P> {
+    let mut destination = Image::new(source.width, source.height);
+    destination.metadata = source.metadata.clone();
+
+    filter_with_view(
+        &source.as_view(),
+        &mut destination.as_view_mut(),
+        radius,
+        sigma_d,
+        sigma_r,
+        options,
+    );
+
+    destination
+}
+
+/// Borders are those of the image the views are taken from, not of the region.
+pub fn filter_with_view<P: GenericPixel>(
+    source: &ImageView<P>,
+    destination: &mut ImageViewMut<P>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    check_views(source, destination);
+
+    let kernel = Kernel {
+        radius,
+        sigma_d,
+        sigma_r,
+    };
+    let neighbours = Interleaved::new(source, &options);
+
+    for i in 0..source.height() {
+        for j in 0..source.width() {
+            let (y, x) = (source.y() + i, source.x() + j);
+
+            *destination.pixel_mut(i, j) = neighbours.filter_pixel(&kernel, y, x, &options);
+        }
+    }
 }
@@ -0,0 +1,420 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use color::ColorSpace;
+use filter::{AlphaMode, BorderMode, Options, Precision};
+use image::{GenericPixel, Image, Pixel, Sample};
+use planar::PlanarImage;
+use view::ImageView;
+
+/// Radius and sigmas of a bilateral filter, which every layout is filtered with.
+#[derive(Clone, Copy, Debug)]
+pub struct Kernel {
+    pub radius: usize,
+    pub sigma_d: f64,
+    pub sigma_r: f64,
+}
+
+/// Pixels a filter weighs and averages, whatever their layout.
+///
+/// `None` stands for the pixels past the edges with `BorderMode::Constant`.
+pub trait Neighbours {
+    /// Handle of a pixel, cheap to copy.
+    type Pixel: Copy;
+
+    /// Height and width of the image.
+    fn size(&self) -> (usize, usize);
+
+    /// Number of channels, at most 4.
+    fn channels(&self) -> usize;
+
+    fn pixel(&self, i: usize, j: usize) -> Self::Pixel;
+
+    fn sample(&self, pixel: Option<Self::Pixel>, channel: usize) -> f64;
+
+    /// Range distance between a pixel and one of its neighbours.
+    fn distance<F: Real>(&self, center: Self::Pixel, neighbour: Option<Self::Pixel>) -> F;
+}
+
+impl Kernel {
+    /// Weighted means of the channels of the pixel at row `i`, column `j`, before they are
+    /// quantized.
+    pub fn filter<N: Neighbours>(
+        &self,
+        source: &N,
+        i: usize,
+        j: usize,
+        options: &Options,
+    ) -> [f64; 4] {
+        match options.precision {
+            Precision::Double => self.filter_in::<N, f64>(source, i, j, options),
+            Precision::Single => self.filter_in::<N, f32>(source, i, j, options),
+        }
+    }
+
+    /// Like `filter`, but in `f64` with neighbours weighed by `weight(dk, dl, distance)`: `dk`
+    /// rows and `dl` columns away from the center, and `distance` away in color.
+    pub fn filter_weighted<N: Neighbours, W: Fn(i32, i32, f64) -> f64>(
+        &self,
+        source: &N,
+        i: usize,
+        j: usize,
+        options: &Options,
+        weight: W,
+    ) -> [f64; 4] {
+        self.accumulate(source, i, j, options, weight)
+    }
+
+    fn filter_in<N: Neighbours, F: Real>(
+        &self,
+        source: &N,
+        i: usize,
+        j: usize,
+        options: &Options,
+    ) -> [f64; 4] {
+        let (sigma_d, sigma_r) = (F::from_f64(self.sigma_d), F::from_f64(self.sigma_r));
+
+        self.accumulate(source, i, j, options, |dk, dl, distance| {
+            weight(dk, dl, distance, sigma_d, sigma_r)
+        })
+    }
+
+    fn accumulate<N: Neighbours, F: Real, W: Fn(i32, i32, F) -> F>(
+        &self,
+        source: &N,
+        i: usize,
+        j: usize,
+        options: &Options,
+        weight: W,
+    ) -> [f64; 4] {
+        let center = source.pixel(i, j);
+        let (height, width) = source.size();
+
+        // Formats have at most 4 channels.
+        let mut values = [F::from_f64(0.0); 4];
+        let mut accum = F::from_f64(0.0);
+
+        for k in options.window.range(i as i32, self.radius as i32) {
+            for l in options.window.range(j as i32, self.radius as i32) {
+                let pixel = match (
+                    options.border.locate(k, height as i32),
+                    options.border.locate(l, width as i32),
+                ) {
+                    (Some(y), Some(x)) => Some(source.pixel(y as usize, x as usize)),
+                    _ if options.border == BorderMode::Constant => None,
+                    _ => continue,
+                };
+
+                let distance = source.distance(center, pixel);
+                let w = weight(k - i as i32, l - j as i32, distance);
+
+                for (channel, value) in values.iter_mut().take(source.channels()).enumerate() {
+                    *value = *value + w * F::from_f64(source.sample(pixel, channel));
+                }
+
+                accum = accum + w;
+            }
+        }
+
+        let mut means = [0f64; 4];
+
+        for (mean, value) in means.iter_mut().zip(&values) {
+            *mean = (*value / accum).to_f64();
+        }
+
+        means
+    }
+}
+
+// Weight of a neighbour `dk` rows and `dl` columns away from the center, and `distance` away
+// in color.
+fn weight<F: Real>(dk: i32, dl: i32, distance: F, sigma_d: F, sigma_r: F) -> F {
+    let two = F::from_f64(2.0);
+    let w_d = F::from_f64((dk * dk + dl * dl) as f64);
+
+    (-w_d / (two * sigma_d * sigma_d) - distance / (two * sigma_r * sigma_r)).exp()
+}
+
+// Quantizes the filtered channels of the pixel at row `i`, column `j`, which is `center`.
+fn to_pixel<P: GenericPixel>(
+    means: &[f64; 4],
+    center: &P,
+    i: usize,
+    j: usize,
+    options: &Options,
+) -> P {
+    let mut result = P::default();
+
+    for (channel, mean) in means.iter().take(P::CHANNELS).enumerate() {
+        let sample = if channel >= P::COLOR_CHANNELS && options.alpha == AlphaMode::Preserve {
+            center.channel(channel)
+        } else {
+            options.quantize(*mean, i as i32, j as i32)
+        };
+
+        result.set_channel(channel, sample);
+    }
+
+    result
+}
+
+/// Pixels of the image a view is taken from, compared by their color channels.
+pub struct Interleaved<'a, P: 'a> {
+    source: ImageView<'a, P>,
+    constant: P,
+}
+
+impl<'a, P: GenericPixel> Interleaved<'a, P> {
+    pub fn new(source: &ImageView<'a, P>, options: &Options) -> Self {
+        let mut constant = P::default();
+
+        for channel in 0..P::CHANNELS {
+            constant.set_channel(channel, P::Sample::from_f64(options.border_value));
+        }
+
+        Interleaved {
+            source: source.context(),
+            constant,
+        }
+    }
+
+    /// Filters the pixel at row `i`, column `j` of the image, not of the view.
+    pub fn filter_pixel(&self, kernel: &Kernel, i: usize, j: usize, options: &Options) -> P {
+        let means = kernel.filter(self, i, j, options);
+
+        to_pixel(&means, self.source.pixel(i, j), i, j, options)
+    }
+
+    /// Like `filter_pixel`, with the weights of `Kernel::filter_weighted`.
+    pub fn filter_pixel_weighted<W: Fn(i32, i32, f64) -> f64>(
+        &self,
+        kernel: &Kernel,
+        i: usize,
+        j: usize,
+        options: &Options,
+        weight: W,
+    ) -> P {
+        let means = kernel.filter_weighted(self, i, j, options, weight);
+
+        to_pixel(&means, self.source.pixel(i, j), i, j, options)
+    }
+}
+
+impl<'a, P: GenericPixel> Neighbours for Interleaved<'a, P> {
+    type Pixel = &'a P;
+
+    fn size(&self) -> (usize, usize) {
+        (self.source.height(), self.source.width())
+    }
+
+    fn channels(&self) -> usize {
+        P::CHANNELS
+    }
+
+    fn pixel(&self, i: usize, j: usize) -> &'a P {
+        self.source.pixel(i, j)
+    }
+
+    fn sample(&self, pixel: Option<&'a P>, channel: usize) -> f64 {
+        pixel.unwrap_or(&self.constant).channel(channel).to_f64()
+    }
+
+    // Only the color channels take part in the range distance: alpha is coverage, not color.
+    fn distance<F: Real>(&self, center: &'a P, neighbour: Option<&'a P>) -> F {
+        let neighbour = neighbour.unwrap_or(&self.constant);
+        let mut distance = F::from_f64(0.0);
+
+        for channel in 0..P::COLOR_CHANNELS {
+            let channel_distance = F::from_f64(center.channel(channel).to_f64())
+                - F::from_f64(neighbour.channel(channel).to_f64());
+            distance = distance + channel_distance * channel_distance;
+        }
+
+        distance.sqrt()
+    }
+}
+
+/// RGB pixels compared by their coordinates in a color space.
+pub struct InSpace<'a, T: 'a> {
+    source: &'a Image<Pixel<T>>,
+    guide: &'a [[f64; 3]],
+    constant: Pixel<T>,
+    constant_coordinates: [f64; 3],
+}
+
+impl<'a, T: Sample> InSpace<'a, T> {
+    /// `guide` holds the coordinates of every pixel of `source` in `space`.
+    pub fn new(
+        source: &'a Image<Pixel<T>>,
+        guide: &'a [[f64; 3]],
+        space: ColorSpace,
+        options: &Options,
+    ) -> Self {
+        let value = T::from_f64(options.border_value);
+        let constant = Pixel {
+            r: value,
+            g: value,
+            b: value,
+        };
+
+        InSpace {
+            source,
+            guide,
+            constant_coordinates: space.coordinates(&constant),
+            constant,
+        }
+    }
+
+    pub fn filter_pixel(&self, kernel: &Kernel, i: usize, j: usize, options: &Options) -> Pixel<T> {
+        let means = kernel.filter(self, i, j, options);
+
+        to_pixel(
+            &means,
+            &self.source.pixels[i * self.source.width + j],
+            i,
+            j,
+            options,
+        )
+    }
+}
+
+impl<'a, T: Sample> Neighbours for InSpace<'a, T> {
+    // Index of the pixel.
+    type Pixel = usize;
+
+    fn size(&self) -> (usize, usize) {
+        (self.source.height, self.source.width)
+    }
+
+    fn channels(&self) -> usize {
+        3
+    }
+
+    fn pixel(&self, i: usize, j: usize) -> usize {
+        i * self.source.width + j
+    }
+
+    fn sample(&self, pixel: Option<usize>, channel: usize) -> f64 {
+        let pixel = pixel.map_or(&self.constant, |index| &self.source.pixels[index]);
+
+        pixel.channel(channel).to_f64()
+    }
+
+    fn distance<F: Real>(&self, center: usize, neighbour: Option<usize>) -> F {
+        let lhs = &self.guide[center];
+        let rhs = neighbour.map_or(&self.constant_coordinates, |index| &self.guide[index]);
+        let mut distance = F::from_f64(0.0);
+
+        for (lhs, rhs) in lhs.iter().zip(rhs) {
+            let coordinate_distance = F::from_f64(*lhs) - F::from_f64(*rhs);
+            distance = distance + coordinate_distance * coordinate_distance;
+        }
+
+        distance.sqrt()
+    }
+}
+
+/// Samples of a planar image, compared over its color channels.
+pub struct Planar<'a, T: 'a> {
+    source: &'a PlanarImage<T>,
+    constant: f64,
+}
+
+impl<'a, T: Sample> Planar<'a, T> {
+    pub fn new(source: &'a PlanarImage<T>, options: &Options) -> Self {
+        assert!(source.channels <= 4, "too many channels for the filter");
+
+        Planar {
+            source,
+            constant: T::from_f64(options.border_value).to_f64(),
+        }
+    }
+}
+
+impl<'a, T: Sample> Neighbours for Planar<'a, T> {
+    // Index of the pixel within every plane.
+    type Pixel = usize;
+
+    fn size(&self) -> (usize, usize) {
+        (self.source.height, self.source.width)
+    }
+
+    fn channels(&self) -> usize {
+        self.source.channels
+    }
+
+    fn pixel(&self, i: usize, j: usize) -> usize {
+        i * self.source.width + j
+    }
+
+    fn sample(&self, pixel: Option<usize>, channel: usize) -> f64 {
+        match pixel {
+            Some(index) => self.source.plane(channel)[index].to_f64(),
+            None => self.constant,
+        }
+    }
+
+    // A fourth plane is alpha, which stays out of the range distance as it does for `Rgba`.
+    fn distance<F: Real>(&self, center: usize, neighbour: Option<usize>) -> F {
+        let channels = self.source.channels.min(3);
+        let mut distance = F::from_f64(0.0);
+
+        for channel in 0..channels {
+            let channel_distance = F::from_f64(self.sample(Some(center), channel))
+                - F::from_f64(self.sample(neighbour, channel));
+            distance = distance + channel_distance * channel_distance;
+        }
+
+        distance.sqrt()
+    }
+}
+
+/// Arithmetic the filters need from `f32` and `f64`, so that one body serves both precisions.
+pub trait Real:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn exp(self) -> Self;
+    fn sqrt(self) -> Self;
+}
+
+impl Real for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn exp(self) -> Self {
+        f64::exp(self)
+    }
+
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+}
+
+impl Real for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn exp(self) -> Self {
+        f32::exp(self)
+    }
+
+    fn sqrt(self) -> Self {
+        f32::sqrt(self)
+    }
+}
@@ -1,5 +1,216 @@
 mod bilateral_sequential;
 pub use self::bilateral_sequential::filter as bilateral_sequential;
+pub use self::bilateral_sequential::filter_in_space as bilateral_sequential_in_space;
+pub use self::bilateral_sequential::filter_rgba as bilateral_sequential_rgba;
+pub use self::bilateral_sequential::filter_luma as bilateral_sequential_luma;
+pub use self::bilateral_sequential::filter_view as bilateral_sequential_view;
+pub use self::bilateral_sequential::filter_rgba_view as bilateral_sequential_rgba_view;
+pub use self::bilateral_sequential::filter_luma_view as bilateral_sequential_luma_view;
+pub use self::bilateral_sequential::filter_planar as bilateral_sequential_planar;
+pub use self::bilateral_sequential::filter_with as bilateral_sequential_with;
+pub use self::bilateral_sequential::filter_with_view as bilateral_sequential_with_view;
 
 mod bilateral_parallel;
-pub use self::bilateral_parallel::filter as bilateral_parallel;
\ No newline at end of file
+pub use self::bilateral_parallel::filter as bilateral_parallel;
+pub use self::bilateral_parallel::filter_in_space as bilateral_parallel_in_space;
+pub use self::bilateral_parallel::filter_rgba as bilateral_parallel_rgba;
+pub use self::bilateral_parallel::filter_luma as bilateral_parallel_luma;
+pub use self::bilateral_parallel::filter_view as bilateral_parallel_view;
+pub use self::bilateral_parallel::filter_rgba_view as bilateral_parallel_rgba_view;
+pub use self::bilateral_parallel::filter_luma_view as bilateral_parallel_luma_view;
+pub use self::bilateral_parallel::filter_planar as bilateral_parallel_planar;
+pub use self::bilateral_parallel::filter_with as bilateral_parallel_with;
+pub use self::bilateral_parallel::filter_with_view as bilateral_parallel_with_view;
+
+mod bilateral_fast;
+pub use self::bilateral_fast::filter as bilateral_fast;
+pub use self::bilateral_fast::filter_with as bilateral_fast_with;
+pub use self::bilateral_fast::filter_with_view as bilateral_fast_with_view;
+pub use self::bilateral_fast::max_deviation as bilateral_fast_max_deviation;
+
+mod bands;
+pub use self::bands::filter_bands;
+
+mod kernel;
+
+use std::ops::Range;
+
+use image::Sample;
+use view::{ImageView, ImageViewMut};
+
+/// Treatment of the alpha channel by the `Rgba` filters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlphaMode {
+    /// Alpha of every pixel is carried through untouched.
+    Preserve,
+    /// Alpha is smoothed with the same weights as the color channels.
+    Filter,
+}
+
+/// Treatment of the neighbours a window would take from outside of the image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BorderMode {
+    /// The window is cut at the edges, so edge pixels average over fewer neighbours.
+    Shrink,
+    /// Pixels past the edges repeat the nearest edge pixel.
+    Replicate,
+    /// The image is reflected about its edge pixels, which aren't repeated.
+    Mirror,
+    /// The image repeats: the left edge goes on at the right one, the top at the bottom.
+    Wrap,
+    /// Pixels past the edges have every channel at `Options::border_value`.
+    Constant,
+}
+
+impl BorderMode {
+    /// Position of the pixel standing in for `index` along an axis of `size` pixels.
+    ///
+    /// `None` for positions outside of the image that `Shrink` skips and `Constant` fills.
+    pub fn locate(self, index: i32, size: i32) -> Option<i32> {
+        if index >= 0 && index < size {
+            return Some(index);
+        }
+
+        match self {
+            BorderMode::Shrink | BorderMode::Constant => None,
+            BorderMode::Replicate => Some(index.min(size - 1).max(0)),
+            BorderMode::Mirror if size == 1 => Some(0),
+            BorderMode::Mirror => {
+                let period = 2 * (size - 1);
+                let index = (index % period + period) % period;
+
+                Some(if index < size { index } else { period - index })
+            }
+            BorderMode::Wrap => Some((index % size + size) % size),
+        }
+    }
+}
+
+/// Extent of the window around every pixel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowMode {
+    /// `-radius..radius`, one pixel short below and to the right of the center.
+    Legacy,
+    /// `-radius..=radius`, centered on the pixel.
+    Symmetric,
+}
+
+impl WindowMode {
+    /// Positions the window covers along an axis, around `center`.
+    pub fn range(self, center: i32, radius: i32) -> Range<i32> {
+        match self {
+            WindowMode::Legacy => center - radius..center + radius,
+            WindowMode::Symmetric => center - radius..center + radius + 1,
+        }
+    }
+}
+
+/// Rounding of filtered values to whole-number samples. Floating point samples are stored
+/// as they are, whatever the mode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Quantization {
+    /// Values are rounded down, which darkens images by half a step on average.
+    Truncate,
+    /// Values are rounded to the nearest sample.
+    Round,
+    /// Values are rounded up or down following an 8x8 Bayer threshold matrix, so that areas
+    /// keep their exact mean brightness. `Options::dither_seed` shifts the matrix.
+    Dither,
+}
+
+/// Floating point type the filters weigh and sum pixels in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Precision {
+    /// `f64` throughout.
+    Double,
+    /// `f32` throughout, which is faster on GPUs and vectorizes twice as wide. Results
+    /// differ from `Double` by at most `1e-5` of white before quantization, so 8-bit results
+    /// are at most one step away, in well under 0.1% of pixels.
+    Single,
+}
+
+/// Behaviour of the filters besides their radius and sigmas.
+///
+/// `Options::default()` is what the plain filters do, and what the reference images were
+/// made with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Options {
+    /// Only matters for formats with channels besides color.
+    pub alpha: AlphaMode,
+    pub border: BorderMode,
+    /// Sample value of the pixels past the edges with `BorderMode::Constant`.
+    pub border_value: f64,
+    pub window: WindowMode,
+    pub quantization: Quantization,
+    /// Selects one of the 64 placements of the threshold matrix with `Quantization::Dither`.
+    pub dither_seed: u32,
+    /// Ignored by the fast filter, which always works from its `f64` tables.
+    pub precision: Precision,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            alpha: AlphaMode::Filter,
+            border: BorderMode::Shrink,
+            border_value: 0.0,
+            window: WindowMode::Legacy,
+            quantization: Quantization::Truncate,
+            dither_seed: 0,
+            precision: Precision::Double,
+        }
+    }
+}
+
+impl Options {
+    /// Options for filtering a part of an image whose first row is `row` of the whole image.
+    ///
+    /// Dithering then quantizes every pixel like it would when filtering the whole image.
+    pub fn starting_at_row(mut self, row: usize) -> Options {
+        let shift = (self.dither_seed / 8 + (row % 8) as u32) % 8;
+
+        self.dither_seed = shift * 8 + self.dither_seed % 8;
+        self
+    }
+
+    /// Converts a filtered `value` of the pixel at row `i`, column `j` to a sample.
+    pub fn quantize<T: Sample>(&self, value: f64, i: i32, j: i32) -> T {
+        if !T::INTEGER {
+            return T::from_f64(value);
+        }
+
+        let offset = match self.quantization {
+            Quantization::Truncate => 0.0,
+            Quantization::Round => 0.5,
+            Quantization::Dither => {
+                let row = (i + (self.dither_seed / 8 % 8) as i32) & 7;
+                let column = (j + (self.dither_seed % 8) as i32) & 7;
+
+                (BAYER[row as usize][column as usize] as f64 + 0.5) / 64.0
+            }
+        };
+
+        T::from_f64(value + offset)
+    }
+}
+
+// Thresholds of ordered dithering, every one of 0..64 spread as evenly as possible.
+const BAYER: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// Makes sure a filter writes exactly as many pixels as it reads.
+fn check_views<P>(source: &ImageView<P>, destination: &ImageViewMut<P>) {
+    assert_eq!(
+        (source.width(), source.height()),
+        (destination.width(), destination.height()),
+        "source and destination views differ in size"
+    );
+}
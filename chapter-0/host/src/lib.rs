@@ -1,7 +1,16 @@
 #![deny(warnings)]
 
+extern crate deflate;
 extern crate png;
 extern crate rayon;
 
+pub mod color;
 pub mod image;
 pub mod filter;
+pub mod generate;
+pub mod metrics;
+pub mod noise;
+pub mod planar;
+pub mod resize;
+pub mod transform;
+pub mod view;
@@ -0,0 +1,193 @@
+use image::{GenericPixel, Image, Pixel, Sample};
+
+// Gaussian window of SSIM, as proposed by Wang et al.
+const SSIM_RADIUS: i32 = 5;
+const SSIM_SIGMA: f64 = 1.5;
+
+/// Differences of a single channel of two images.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelStats {
+    pub mse: f64,
+    pub max_abs_error: f64,
+    /// Mean of `current - reference`: a bias of one image against the other.
+    pub mean_error: f64,
+    /// Number of samples which are not exactly equal.
+    pub different: usize,
+}
+
+/// Statistics of `current - reference` for every channel, in channel order.
+///
+/// Panics if the images differ in size, as does every other metric.
+pub fn channel_stats<P: GenericPixel>(
+    current: &Image<P>,
+    reference: &Image<P>,
+) -> Vec<ChannelStats> {
+    check_sizes(current, reference);
+
+    let count = current.pixels.len().max(1) as f64;
+
+    (0..P::CHANNELS)
+        .map(|channel| {
+            let mut stats = ChannelStats {
+                mse: 0.0,
+                max_abs_error: 0.0,
+                mean_error: 0.0,
+                different: 0,
+            };
+
+            for (lhs, rhs) in current.pixels.iter().zip(reference.pixels.iter()) {
+                let error = lhs.channel(channel).to_f64() - rhs.channel(channel).to_f64();
+
+                stats.mse += error * error;
+                stats.mean_error += error;
+                stats.max_abs_error = stats.max_abs_error.max(error.abs());
+
+                if lhs.channel(channel) != rhs.channel(channel) {
+                    stats.different += 1;
+                }
+            }
+
+            stats.mse /= count;
+            stats.mean_error /= count;
+            stats
+        })
+        .collect()
+}
+
+/// Mean squared error over all channels.
+pub fn mse<P: GenericPixel>(current: &Image<P>, reference: &Image<P>) -> f64 {
+    let stats = channel_stats(current, reference);
+
+    stats.iter().map(|stats| stats.mse).sum::<f64>() / stats.len() as f64
+}
+
+/// Peak signal-to-noise ratio in decibels, relative to the white level of the samples.
+///
+/// Identical images are infinitely far apart from noise.
+pub fn psnr<P: GenericPixel>(current: &Image<P>, reference: &Image<P>) -> f64 {
+    let mse = mse(current, reference);
+
+    if mse == 0.0 {
+        return f64::INFINITY;
+    }
+
+    let white = P::Sample::WHITE;
+    10.0 * (white * white / mse).log10()
+}
+
+/// Largest difference of any sample.
+pub fn max_abs_error<P: GenericPixel>(current: &Image<P>, reference: &Image<P>) -> f64 {
+    channel_stats(current, reference)
+        .iter()
+        .map(|stats| stats.max_abs_error)
+        .fold(0.0, f64::max)
+}
+
+/// Mean structural similarity over all channels, 1 for identical images.
+///
+/// Windows are Gaussian with `sigma = 1.5` over 11 by 11 pixels, and cropped at the border of
+/// the image instead of skipping pixels whose window doesn't fit.
+pub fn ssim<P: GenericPixel>(current: &Image<P>, reference: &Image<P>) -> f64 {
+    check_sizes(current, reference);
+
+    let (width, height) = (current.width as i32, current.height as i32);
+
+    let white = P::Sample::WHITE;
+    let c1 = (0.01 * white) * (0.01 * white);
+    let c2 = (0.03 * white) * (0.03 * white);
+
+    let weights: Vec<f64> = (-SSIM_RADIUS..SSIM_RADIUS + 1)
+        .map(|offset| (-(offset * offset) as f64 / (2.0 * SSIM_SIGMA * SSIM_SIGMA)).exp())
+        .collect();
+
+    let mut total = 0f64;
+
+    for channel in 0..P::CHANNELS {
+        let sample = |image: &Image<P>, i: i32, j: i32| {
+            image.pixels[(i * width + j) as usize]
+                .channel(channel)
+                .to_f64()
+        };
+
+        for i in 0..height {
+            for j in 0..width {
+                let (mut mean_x, mut mean_y) = (0f64, 0f64);
+                let (mut square_x, mut square_y, mut product) = (0f64, 0f64, 0f64);
+                let mut accum = 0f64;
+
+                for k in (i - SSIM_RADIUS).max(0)..(i + SSIM_RADIUS + 1).min(height) {
+                    for l in (j - SSIM_RADIUS).max(0)..(j + SSIM_RADIUS + 1).min(width) {
+                        let w = weights[(k - i + SSIM_RADIUS) as usize]
+                            * weights[(l - j + SSIM_RADIUS) as usize];
+
+                        let x = sample(current, k, l);
+                        let y = sample(reference, k, l);
+
+                        mean_x += w * x;
+                        mean_y += w * y;
+                        square_x += w * x * x;
+                        square_y += w * y * y;
+                        product += w * x * y;
+                        accum += w;
+                    }
+                }
+
+                let (mean_x, mean_y) = (mean_x / accum, mean_y / accum);
+                let variance_x = square_x / accum - mean_x * mean_x;
+                let variance_y = square_y / accum - mean_y * mean_y;
+                let covariance = product / accum - mean_x * mean_y;
+
+                total += ((2.0 * mean_x * mean_y + c1) * (2.0 * covariance + c2))
+                    / ((mean_x * mean_x + mean_y * mean_y + c1) * (variance_x + variance_y + c2));
+            }
+        }
+    }
+
+    total / (current.pixels.len() * P::CHANNELS) as f64
+}
+
+/// Renders the largest channel difference of every pixel with the "hot" color map.
+///
+/// Equal pixels are black, and errors go through red and yellow up to white at `full_scale`.
+pub fn diff_image<P: GenericPixel>(
+    current: &Image<P>,
+    reference: &Image<P>,
+    full_scale: f64,
+) -> Image {
+    check_sizes(current, reference);
+
+    let mut diff = Image::new(current.width, current.height);
+
+    for (index, (lhs, rhs)) in current
+        .pixels
+        .iter()
+        .zip(reference.pixels.iter())
+        .enumerate()
+    {
+        let error = (0..P::CHANNELS)
+            .map(|channel| (lhs.channel(channel).to_f64() - rhs.channel(channel).to_f64()).abs())
+            .fold(0.0, f64::max);
+
+        diff.pixels[index] = hot(error / full_scale);
+    }
+
+    diff
+}
+
+fn hot(value: f64) -> Pixel {
+    let ramp = |offset: f64| ((3.0 * value - offset).clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    Pixel {
+        r: ramp(0.0),
+        g: ramp(1.0),
+        b: ramp(2.0),
+    }
+}
+
+fn check_sizes<P>(current: &Image<P>, reference: &Image<P>) {
+    assert_eq!(
+        (current.width, current.height),
+        (reference.width, reference.height),
+        "images differ in size"
+    );
+}
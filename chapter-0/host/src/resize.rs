@@ -0,0 +1,248 @@
+use rayon::prelude::*;
+use std::f64;
+
+use image::{GenericPixel, Image, Metadata, Sample};
+
+/// Kernel weighing source pixels around every destination pixel.
+///
+/// Kernels are stretched when shrinking, so that every source pixel contributes and fine
+/// detail averages out instead of aliasing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    /// Triangle over the two nearest pixels.
+    Bilinear,
+    /// Catmull-Rom cubic (`a = -0.5`) over the four nearest pixels.
+    Bicubic,
+    /// Windowed sinc over the six nearest pixels.
+    Lanczos3,
+}
+
+impl Interpolation {
+    /// Distance from the center at which the kernel drops to zero, in source pixels.
+    pub fn support(self) -> f64 {
+        match self {
+            Interpolation::Bilinear => 1.0,
+            Interpolation::Bicubic => 2.0,
+            Interpolation::Lanczos3 => 3.0,
+        }
+    }
+
+    /// Weight of a pixel `x` pixels away from the sampled position, 1 at 0.
+    pub fn weight(self, x: f64) -> f64 {
+        let x = x.abs();
+
+        if x >= self.support() {
+            return 0.0;
+        }
+
+        match self {
+            Interpolation::Bilinear => 1.0 - x,
+            Interpolation::Bicubic => {
+                let a = -0.5;
+
+                if x < 1.0 {
+                    ((a + 2.0) * x - (a + 3.0)) * x * x + 1.0
+                } else {
+                    ((a * x - 5.0 * a) * x + 8.0 * a) * x - 4.0 * a
+                }
+            }
+            Interpolation::Lanczos3 => {
+                // `sin(πx)` isn't exactly zero at whole numbers, which would leak neighbours
+                // into resizes by whole factors.
+                if x == 0.0 {
+                    1.0
+                } else if x.fract() == 0.0 {
+                    0.0
+                } else {
+                    let pi_x = f64::consts::PI * x;
+                    3.0 * pi_x.sin() * (pi_x / 3.0).sin() / (pi_x * pi_x)
+                }
+            }
+        }
+    }
+}
+
+/// Resamples `source` to `width` by `height` pixels.
+///
+/// Every channel is resampled on its own, alpha included. Overshoot of `Bicubic` and
+/// `Lanczos3` is clipped to the range the channel has in `source`, and integer samples are
+/// rounded to the nearest value. Pixels past the border repeat the edge.
+/// Physical pixel dimensions are scaled so that the image keeps its size.
+pub fn resize<P: GenericPixel>(
+    source: &Image<P>,
+    width: usize,
+    height: usize,
+    interpolation: Interpolation,
+) -> Image<P> {
+    let plan = Plan::new(source, width, height, interpolation);
+
+    let rows: Vec<_> = (0..source.height * width)
+        .map(|index| plan.horizontal(source, index))
+        .collect();
+
+    let mut destination = Image::new(width, height);
+    destination.metadata = resized_metadata(source, width, height);
+    destination.pixels = (0..height * width)
+        .map(|index| plan.vertical(&rows, index))
+        .collect();
+
+    destination
+}
+
+/// Like `resize`, but both passes run on all cores.
+pub fn resize_parallel<P: GenericPixel + Send + Sync>(
+    source: &Image<P>,
+    width: usize,
+    height: usize,
+    interpolation: Interpolation,
+) -> Image<P> {
+    let plan = Plan::new(source, width, height, interpolation);
+
+    let rows: Vec<_> = (0..source.height * width)
+        .into_par_iter()
+        .map(|index| plan.horizontal(source, index))
+        .collect();
+
+    let mut destination = Image::new(width, height);
+    destination.metadata = resized_metadata(source, width, height);
+    destination.pixels = (0..height * width)
+        .into_par_iter()
+        .map(|index| plan.vertical(&rows, index))
+        .collect();
+
+    destination
+}
+
+// Resizing is separable: rows are resampled to the new width first, then columns of the
+// result to the new height.
+struct Plan {
+    columns: Vec<Vec<(usize, f64)>>,
+    rows: Vec<Vec<(usize, f64)>>,
+    width: usize,
+    low: [f64; 4],
+    high: [f64; 4],
+}
+
+impl Plan {
+    fn new<P: GenericPixel>(
+        source: &Image<P>,
+        width: usize,
+        height: usize,
+        interpolation: Interpolation,
+    ) -> Self {
+        assert!(
+            width * height == 0 || source.width * source.height > 0,
+            "cannot resize an empty image"
+        );
+
+        let mut low = [f64::INFINITY; 4];
+        let mut high = [f64::NEG_INFINITY; 4];
+
+        for pixel in &source.pixels {
+            for channel in 0..P::CHANNELS {
+                let value = pixel.channel(channel).to_f64();
+                low[channel] = low[channel].min(value);
+                high[channel] = high[channel].max(value);
+            }
+        }
+
+        Plan {
+            columns: taps(source.width, width, interpolation),
+            rows: taps(source.height, height, interpolation),
+            width,
+            low,
+            high,
+        }
+    }
+
+    fn horizontal<P: GenericPixel>(&self, source: &Image<P>, index: usize) -> [f64; 4] {
+        let (i, j) = (index / self.width, index % self.width);
+        let mut value = [0f64; 4];
+
+        for &(column, weight) in &self.columns[j] {
+            let pixel = &source.pixels[i * source.width + column];
+
+            for (channel, value) in value.iter_mut().take(P::CHANNELS).enumerate() {
+                *value += weight * pixel.channel(channel).to_f64();
+            }
+        }
+
+        value
+    }
+
+    fn vertical<P: GenericPixel>(&self, rows: &[[f64; 4]], index: usize) -> P {
+        let (i, j) = (index / self.width, index % self.width);
+        let mut pixel = P::default();
+
+        let ranges = self.low.iter().zip(&self.high).take(P::CHANNELS);
+
+        for (channel, (&low, &high)) in ranges.enumerate() {
+            let mut value = 0f64;
+
+            for &(row, weight) in &self.rows[i] {
+                value += weight * rows[row * self.width + j][channel];
+            }
+
+            let mut value = value.max(low).min(high);
+
+            if P::Sample::INTEGER {
+                value = value.round();
+            }
+
+            pixel.set_channel(channel, P::Sample::from_f64(value));
+        }
+
+        pixel
+    }
+}
+
+// Source pixels and normalized weights for every destination pixel along one axis.
+fn taps(source: usize, destination: usize, interpolation: Interpolation) -> Vec<Vec<(usize, f64)>> {
+    let scale = source as f64 / destination as f64;
+    let stretch = scale.max(1.0);
+    let support = interpolation.support() * stretch;
+
+    (0..destination)
+        .map(|x| {
+            // Pixel centers line up, rather than the corners of the first pixels.
+            let center = (x as f64 + 0.5) * scale - 0.5;
+            let first = (center - support).ceil() as isize;
+            let last = (center + support).floor() as isize;
+
+            let mut taps = Vec::new();
+            let mut total = 0f64;
+
+            for k in first..last + 1 {
+                let weight = interpolation.weight((k as f64 - center) / stretch);
+
+                if weight != 0.0 {
+                    let index = k.max(0).min(source as isize - 1) as usize;
+
+                    taps.push((index, weight));
+                    total += weight;
+                }
+            }
+
+            for tap in &mut taps {
+                tap.1 /= total;
+            }
+
+            taps
+        })
+        .collect()
+}
+
+fn resized_metadata<P>(source: &Image<P>, width: usize, height: usize) -> Metadata {
+    let mut metadata = source.metadata.clone();
+
+    if let Some(ref mut dimensions) = metadata.physical_dimensions {
+        let scale = |value: u32, from: usize, to: usize| {
+            (value as f64 * to as f64 / from.max(1) as f64).round() as u32
+        };
+
+        dimensions.x_pixels_per_unit = scale(dimensions.x_pixels_per_unit, source.width, width);
+        dimensions.y_pixels_per_unit = scale(dimensions.y_pixels_per_unit, source.height, height);
+    }
+
+    metadata
+}
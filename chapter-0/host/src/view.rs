@@ -0,0 +1,297 @@
+use image::Image;
+
+/// Borrowed rectangular region of a buffer of pixels, whose rows are `stride` pixels apart.
+///
+/// The region remembers the whole buffer it was taken from, so filters can look at pixels
+/// around it, while only ever touching the region itself.
+pub struct ImageView<'a, P: 'a> {
+    pixels: &'a [P],
+    stride: usize,
+    full_width: usize,
+    full_height: usize,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+/// Mutable counterpart of `ImageView`.
+pub struct ImageViewMut<'a, P: 'a> {
+    pixels: &'a mut [P],
+    stride: usize,
+    full_width: usize,
+    full_height: usize,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<P> Image<P> {
+    pub fn as_view<'a>(&'a self) -> ImageView<'a, P> {
+        ImageView::new(&self.pixels, self.width, self.height, self.width)
+    }
+
+    pub fn as_view_mut<'a>(&'a mut self) -> ImageViewMut<'a, P> {
+        let (width, height) = (self.width, self.height);
+
+        ImageViewMut::new(&mut self.pixels, width, height, width)
+    }
+
+    /// Borrows the `width` by `height` region whose top left pixel is at column `x`, row `y`.
+    pub fn view<'a>(&'a self, x: usize, y: usize, width: usize, height: usize) -> ImageView<'a, P> {
+        self.as_view().view(x, y, width, height)
+    }
+
+    pub fn view_mut<'a>(
+        &'a mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> ImageViewMut<'a, P> {
+        self.as_view_mut().into_view_mut(x, y, width, height)
+    }
+}
+
+impl<'a, P> ImageView<'a, P> {
+    /// Views the whole of a `width` by `height` buffer, with rows `stride` pixels apart.
+    pub fn new(pixels: &'a [P], width: usize, height: usize, stride: usize) -> Self {
+        check_buffer(pixels.len(), width, height, stride);
+
+        ImageView {
+            pixels,
+            stride,
+            full_width: width,
+            full_height: height,
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    /// Narrows the view down to a region of it, at column `x`, row `y` of the current region.
+    pub fn view(&self, x: usize, y: usize, width: usize, height: usize) -> ImageView<'a, P> {
+        check_region(x, y, width, height, self.width, self.height);
+
+        ImageView {
+            x: self.x + x,
+            y: self.y + y,
+            width,
+            height,
+            ..*self
+        }
+    }
+
+    /// The whole buffer the region was taken from.
+    pub fn context(&self) -> ImageView<'a, P> {
+        ImageView {
+            x: 0,
+            y: 0,
+            width: self.full_width,
+            height: self.full_height,
+            ..*self
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Column of the region in the whole buffer.
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    /// Row of the region in the whole buffer.
+    pub fn y(&self) -> usize {
+        self.y
+    }
+
+    /// Index of the top left pixel of the region in the buffer.
+    pub fn offset(&self) -> usize {
+        self.y * self.stride + self.x
+    }
+
+    pub fn pixel(&self, i: usize, j: usize) -> &'a P {
+        assert!(
+            i < self.height && j < self.width,
+            "pixel is out of the view"
+        );
+
+        &self.pixels[self.offset() + i * self.stride + j]
+    }
+
+    pub fn row(&self, i: usize) -> &'a [P] {
+        assert!(i < self.height, "row is out of the view");
+
+        let start = self.offset() + i * self.stride;
+        &self.pixels[start..start + self.width]
+    }
+}
+
+impl<'a, P: Clone + Default> ImageView<'a, P> {
+    /// Copies the region into an image of its own.
+    pub fn to_image(&self) -> Image<P> {
+        let mut image = Image::new(self.width, self.height);
+
+        for i in 0..self.height {
+            image.pixels[i * self.width..(i + 1) * self.width].clone_from_slice(self.row(i));
+        }
+
+        image
+    }
+}
+
+impl<'a, P> Clone for ImageView<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P> Copy for ImageView<'a, P> {}
+
+impl<'a, P> ImageViewMut<'a, P> {
+    /// Views the whole of a `width` by `height` buffer, with rows `stride` pixels apart.
+    pub fn new(pixels: &'a mut [P], width: usize, height: usize, stride: usize) -> Self {
+        check_buffer(pixels.len(), width, height, stride);
+
+        ImageViewMut {
+            pixels,
+            stride,
+            full_width: width,
+            full_height: height,
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    /// Reborrows a region of the view, at column `x`, row `y` of the current region.
+    pub fn view_mut<'b>(
+        &'b mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> ImageViewMut<'b, P> {
+        check_region(x, y, width, height, self.width, self.height);
+
+        ImageViewMut {
+            pixels: &mut *self.pixels,
+            stride: self.stride,
+            full_width: self.full_width,
+            full_height: self.full_height,
+            x: self.x + x,
+            y: self.y + y,
+            width,
+            height,
+        }
+    }
+
+    /// Narrows the view down to a region of it, keeping the borrow of the buffer.
+    pub fn into_view_mut(self, x: usize, y: usize, width: usize, height: usize) -> Self {
+        check_region(x, y, width, height, self.width, self.height);
+
+        ImageViewMut {
+            x: self.x + x,
+            y: self.y + y,
+            width,
+            height,
+            ..self
+        }
+    }
+
+    pub fn as_view<'b>(&'b self) -> ImageView<'b, P> {
+        ImageView {
+            pixels: &*self.pixels,
+            stride: self.stride,
+            full_width: self.full_width,
+            full_height: self.full_height,
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Column of the region in the whole buffer.
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    /// Row of the region in the whole buffer.
+    pub fn y(&self) -> usize {
+        self.y
+    }
+
+    /// Index of the top left pixel of the region in the buffer.
+    pub fn offset(&self) -> usize {
+        self.y * self.stride + self.x
+    }
+
+    pub fn pixel_mut(&mut self, i: usize, j: usize) -> &mut P {
+        assert!(
+            i < self.height && j < self.width,
+            "pixel is out of the view"
+        );
+
+        let index = self.offset() + i * self.stride + j;
+        &mut self.pixels[index]
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> &mut [P] {
+        assert!(i < self.height, "row is out of the view");
+
+        let start = self.offset() + i * self.stride;
+        &mut self.pixels[start..start + self.width]
+    }
+}
+
+fn check_buffer(length: usize, width: usize, height: usize, stride: usize) {
+    assert!(stride >= width, "rows overlap each other");
+
+    // The last row doesn't need to be padded up to the stride.
+    if width > 0 && height > 0 {
+        assert!(
+            length >= (height - 1) * stride + width,
+            "buffer is too short"
+        );
+    }
+}
+
+fn check_region(
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    outer_width: usize,
+    outer_height: usize,
+) {
+    assert!(
+        x + width <= outer_width && y + height <= outer_height,
+        "region is out of the view"
+    );
+}
@@ -0,0 +1,206 @@
+extern crate chapter_0;
+
+use std::fs::File;
+use std::io::BufReader;
+
+use chapter_0::filter::{
+    bilateral_parallel, bilateral_parallel_with, bilateral_sequential_luma,
+    bilateral_sequential_with, filter_bands, BorderMode, Options, Quantization, WindowMode,
+};
+use chapter_0::image::{Format, Image, ImageError, Luma, Pixel, RowReader, RowWriter};
+
+fn stream_png(input: &[u8], band_height: usize, radius: usize) -> Vec<u8> {
+    let mut reader = RowReader::<Pixel, _>::new(input).unwrap();
+    let mut writer = RowWriter::new(
+        Vec::new(),
+        Format::Png,
+        reader.width,
+        reader.height,
+        &reader.metadata,
+    )
+    .unwrap();
+
+    filter_bands(&mut reader, &mut writer, band_height, radius, |band, _| {
+        bilateral_parallel(band, radius, 3.5, 3.0)
+    })
+    .unwrap();
+
+    writer.finish().unwrap()
+}
+
+#[test]
+fn should_read_rows_like_whole_image() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let file = BufReader::new(File::open("../../fixtures/input-512.png").unwrap());
+    let mut reader = RowReader::<Pixel, _>::new(file).unwrap();
+
+    assert_eq!((reader.width, reader.height), (image.width, image.height));
+    assert_eq!(reader.metadata, image.metadata);
+
+    let mut row = vec![Pixel::default(); reader.width];
+
+    for i in 0..reader.height {
+        reader.read_row(&mut row).unwrap();
+        assert!(row[..] == image.pixels[i * image.width..(i + 1) * image.width]);
+    }
+
+    match reader.read_row(&mut row) {
+        Err(ImageError::TruncatedData) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn should_write_rows_like_whole_image() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+
+    for format in &[Format::Png, Format::Ppm, Format::Pam] {
+        let mut writer = RowWriter::new(
+            Vec::new(),
+            *format,
+            image.width,
+            image.height,
+            &image.metadata,
+        )
+        .unwrap();
+
+        for row in image.pixels.chunks(image.width) {
+            writer.write_row(row).unwrap();
+        }
+
+        let decoded: Image = Image::from_bytes(&writer.finish().unwrap()).unwrap();
+
+        assert!(decoded.pixels == image.pixels);
+    }
+}
+
+#[test]
+fn should_match_whole_image_filtering_512() {
+    let input = include_bytes!("../../../fixtures/input-512.png");
+    let image: Image = Image::from_bytes(input).unwrap();
+    let reference_output = bilateral_parallel(&image, 5, 3.5, 3.0);
+
+    for &band_height in &[1, 7, 64, 512, 1000] {
+        let output: Image = Image::from_bytes(&stream_png(input, band_height, 5)).unwrap();
+
+        assert!(output.pixels == reference_output.pixels);
+        assert_eq!(output.metadata, image.metadata);
+    }
+}
+
+#[test]
+fn should_match_whole_image_filtering_netpbm() {
+    let mut image = Image::<Luma>::new(96, 80);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = (index * 7 % 251) as u8;
+    }
+
+    let reference_output = bilateral_sequential_luma(&image, 3, 3.5, 30.0);
+    let input = image.to_bytes(Format::Pgm).unwrap();
+
+    let mut reader = RowReader::<Luma, _>::new(&input[..]).unwrap();
+    let mut writer = RowWriter::new(Vec::new(), Format::Pgm, 96, 80, &reader.metadata).unwrap();
+
+    filter_bands(&mut reader, &mut writer, 5, 3, |band, _| {
+        bilateral_sequential_luma(band, 3, 3.5, 30.0)
+    })
+    .unwrap();
+
+    let output: Image<Luma> = Image::from_bytes(&writer.finish().unwrap()).unwrap();
+
+    assert!(output.pixels == reference_output.pixels);
+}
+
+#[test]
+fn should_match_whole_image_filtering_past_edges() {
+    let mut image = Image::<Luma>::new(48, 40);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = (index * 7 % 251) as u8;
+    }
+
+    let input = image.to_bytes(Format::Pgm).unwrap();
+
+    for &border in &[
+        BorderMode::Replicate,
+        BorderMode::Mirror,
+        BorderMode::Constant,
+    ] {
+        let options = Options {
+            border,
+            border_value: 255.0,
+            window: WindowMode::Symmetric,
+            ..Options::default()
+        };
+        let reference_output = bilateral_sequential_with(&image, 3, 3.5, 30.0, options);
+
+        for &band_height in &[1, 5, 40] {
+            let mut reader = RowReader::<Luma, _>::new(&input[..]).unwrap();
+            let mut writer =
+                RowWriter::new(Vec::new(), Format::Pgm, 48, 40, &reader.metadata).unwrap();
+
+            filter_bands(&mut reader, &mut writer, band_height, 3, |band, _| {
+                bilateral_sequential_with(band, 3, 3.5, 30.0, options)
+            })
+            .unwrap();
+
+            let output: Image<Luma> = Image::from_bytes(&writer.finish().unwrap()).unwrap();
+
+            assert!(
+                output.pixels == reference_output.pixels,
+                "{:?}, bands of {}",
+                border,
+                band_height
+            );
+        }
+    }
+}
+
+#[test]
+fn should_line_up_dithering_across_bands() {
+    let input = include_bytes!("../../../fixtures/input-512.png");
+    let image: Image = Image::from_bytes(input).unwrap();
+
+    for &dither_seed in &[0, 21] {
+        let options = Options {
+            quantization: Quantization::Dither,
+            dither_seed,
+            ..Options::default()
+        };
+        let reference_output = bilateral_parallel_with(&image, 5, 3.5, 30.0, options);
+
+        for &band_height in &[1, 10, 512] {
+            let mut reader = RowReader::<Pixel, _>::new(&input[..]).unwrap();
+            let mut writer =
+                RowWriter::new(Vec::new(), Format::Png, 512, 512, &reader.metadata).unwrap();
+
+            filter_bands(&mut reader, &mut writer, band_height, 5, |band, row| {
+                bilateral_parallel_with(band, 5, 3.5, 30.0, options.starting_at_row(row))
+            })
+            .unwrap();
+
+            let output: Image = Image::from_bytes(&writer.finish().unwrap()).unwrap();
+
+            assert!(
+                output.pixels == reference_output.pixels,
+                "seed {}, bands of {}",
+                dither_seed,
+                band_height
+            );
+        }
+    }
+}
+
+#[test]
+fn should_reject_unfinished_image() {
+    let mut writer =
+        RowWriter::<Pixel, _>::new(Vec::new(), Format::Png, 4, 2, &Default::default()).unwrap();
+
+    writer.write_row(&vec![Pixel::default(); 4]).unwrap();
+
+    match writer.finish() {
+        Err(ImageError::TruncatedData) => {}
+        result => panic!("unexpected result: {:?}", result.map(|_| ())),
+    }
+}
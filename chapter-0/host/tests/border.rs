@@ -0,0 +1,180 @@
+extern crate chapter_0;
+
+mod utils;
+
+use chapter_0::filter::{
+    bilateral_parallel, bilateral_parallel_with, bilateral_sequential_with, AlphaMode, BorderMode,
+    Options,
+};
+use chapter_0::generate;
+use chapter_0::image::{Image, Pixel, Rgba};
+use chapter_0::transform::{crop, pad, Padding};
+
+use utils::compare_images;
+
+const RADIUS: usize = 4;
+
+fn options(border: BorderMode) -> Options {
+    Options {
+        border,
+        ..Options::default()
+    }
+}
+
+// Filters a copy padded by the radius, where shrinking the window never kicks in, and crops
+// the padding off again.
+fn filter_padded(image: &Image, padding: &Padding<Pixel>) -> Image {
+    let padded = pad(image, RADIUS, RADIUS, RADIUS, RADIUS, padding);
+    let filtered = bilateral_parallel(&padded, RADIUS, 3.5, 30.0);
+
+    crop(&filtered, RADIUS, RADIUS, image.width, image.height)
+}
+
+fn tile(image: &Image) -> Image {
+    let (width, height) = (image.width * 3, image.height * 3);
+    let mut tiled = Image::new(width, height);
+
+    for i in 0..height {
+        for j in 0..width {
+            tiled.pixels[i * width + j] =
+                image.pixels[(i % image.height) * image.width + j % image.width].clone();
+        }
+    }
+
+    tiled
+}
+
+#[test]
+fn should_shrink_by_default() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    compare_images(
+        &bilateral_sequential_with(&input, 5, 3.5, 3.0, Options::default()),
+        &reference_output,
+    );
+    compare_images(
+        &bilateral_parallel_with(&input, 5, 3.5, 3.0, options(BorderMode::Shrink)),
+        &reference_output,
+    );
+}
+
+#[test]
+fn should_agree_between_backends() {
+    let image: Image = generate::photographic(53, 31, 1);
+
+    for &border in &[
+        BorderMode::Shrink,
+        BorderMode::Replicate,
+        BorderMode::Mirror,
+        BorderMode::Wrap,
+        BorderMode::Constant,
+    ] {
+        let options = Options {
+            border,
+            border_value: 40.0,
+            ..Options::default()
+        };
+
+        compare_images(
+            &bilateral_sequential_with(&image, RADIUS, 3.5, 30.0, options),
+            &bilateral_parallel_with(&image, RADIUS, 3.5, 30.0, options),
+        );
+    }
+}
+
+#[test]
+fn should_match_padded_filtering() {
+    let image: Image = generate::photographic(40, 24, 2);
+
+    compare_images(
+        &bilateral_sequential_with(&image, RADIUS, 3.5, 30.0, options(BorderMode::Replicate)),
+        &filter_padded(&image, &Padding::Replicate),
+    );
+    compare_images(
+        &bilateral_sequential_with(&image, RADIUS, 3.5, 30.0, options(BorderMode::Mirror)),
+        &filter_padded(&image, &Padding::Mirror),
+    );
+
+    let constant = Options {
+        border: BorderMode::Constant,
+        border_value: 200.0,
+        ..Options::default()
+    };
+    let padding = Padding::Constant(Pixel {
+        r: 200,
+        g: 200,
+        b: 200,
+    });
+
+    compare_images(
+        &bilateral_sequential_with(&image, RADIUS, 3.5, 30.0, constant),
+        &filter_padded(&image, &padding),
+    );
+}
+
+#[test]
+fn should_match_tiled_filtering() {
+    let image: Image = generate::photographic(24, 16, 3);
+
+    let tiled = bilateral_parallel(&tile(&image), RADIUS, 3.5, 30.0);
+
+    compare_images(
+        &bilateral_sequential_with(&image, RADIUS, 3.5, 30.0, options(BorderMode::Wrap)),
+        &crop(&tiled, image.width, image.height, image.width, image.height),
+    );
+}
+
+#[test]
+fn should_locate_border_pixels() {
+    assert_eq!(BorderMode::Shrink.locate(-1, 4), None);
+    assert_eq!(BorderMode::Constant.locate(4, 4), None);
+    assert_eq!(BorderMode::Constant.locate(3, 4), Some(3));
+
+    let positions = |border: BorderMode| -> Vec<_> {
+        (-5..9)
+            .map(|index| border.locate(index, 4).unwrap())
+            .collect()
+    };
+
+    assert_eq!(
+        positions(BorderMode::Replicate),
+        vec![0, 0, 0, 0, 0, 0, 1, 2, 3, 3, 3, 3, 3, 3]
+    );
+    assert_eq!(
+        positions(BorderMode::Mirror),
+        vec![1, 2, 3, 2, 1, 0, 1, 2, 3, 2, 1, 0, 1, 2]
+    );
+    assert_eq!(
+        positions(BorderMode::Wrap),
+        vec![3, 0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3, 0]
+    );
+    assert_eq!(BorderMode::Mirror.locate(-3, 1), Some(0));
+}
+
+#[test]
+fn should_preserve_alpha_with_borders() {
+    let image: Image = generate::photographic(20, 20, 4);
+    let mut rgba = Image::<Rgba>::new(20, 20);
+
+    for (index, (target, pixel)) in rgba.pixels.iter_mut().zip(image.pixels.iter()).enumerate() {
+        *target = Rgba {
+            r: pixel.r,
+            g: pixel.g,
+            b: pixel.b,
+            a: (index * 7 % 256) as u8,
+        };
+    }
+
+    let options = Options {
+        alpha: AlphaMode::Preserve,
+        border: BorderMode::Mirror,
+        ..Options::default()
+    };
+
+    let filtered = bilateral_parallel_with(&rgba, RADIUS, 3.5, 30.0, options);
+
+    for (lhs, rhs) in filtered.pixels.iter().zip(rgba.pixels.iter()) {
+        assert_eq!(lhs.a, rhs.a);
+    }
+}
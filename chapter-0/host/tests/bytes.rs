@@ -0,0 +1,72 @@
+extern crate chapter_0;
+
+use std::mem::size_of;
+
+use chapter_0::image::{Image, Luma, Pixel, Plain, Rgba};
+
+#[test]
+fn should_lay_out_pixels_as_samples() {
+    assert_eq!(size_of::<Pixel<u16>>(), 6);
+    assert_eq!(size_of::<Rgba<f32>>(), 16);
+    assert_eq!(size_of::<Luma<u8>>(), 1);
+
+    let pixels = [
+        Pixel::<u16> {
+            r: 0x0102,
+            g: 0x0304,
+            b: 0x0506,
+        },
+        Pixel::<u16> {
+            r: 0x0708,
+            g: 0x090a,
+            b: 0x0b0c,
+        },
+    ];
+
+    let samples: Vec<u16> = Plain::as_bytes(&pixels)
+        .chunks(2)
+        .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]))
+        .collect();
+
+    assert_eq!(
+        samples,
+        vec![0x0102, 0x0304, 0x0506, 0x0708, 0x090a, 0x0b0c]
+    );
+}
+
+#[test]
+fn should_write_pixels_through_bytes() {
+    let mut image = Image::<Rgba>::new(2, 1);
+
+    image
+        .as_bytes_mut()
+        .copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+
+    assert_eq!(
+        (
+            image.pixels[1].r,
+            image.pixels[1].g,
+            image.pixels[1].b,
+            image.pixels[1].a
+        ),
+        (5, 6, 7, 8)
+    );
+    assert_eq!(image.as_bytes(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn should_check_viewed_bytes() {
+    let samples = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let bytes = f32::as_bytes(&samples);
+
+    let pixels = Pixel::<f32>::from_bytes(bytes).unwrap();
+
+    assert_eq!(pixels.len(), 2);
+    assert_eq!((pixels[1].r, pixels[1].g, pixels[1].b), (4.0, 5.0, 6.0));
+
+    // Not a whole number of pixels.
+    assert!(Pixel::<f32>::from_bytes(&bytes[..20]).is_none());
+
+    // Samples not starting on their alignment.
+    assert!(Luma::<f32>::from_bytes(&bytes[1..5]).is_none());
+}
@@ -0,0 +1,209 @@
+extern crate chapter_0;
+
+use chapter_0::color::{
+    lab_to_rgb, linear_to_srgb, rgb_to_lab, rgb_to_ycbcr, srgb_to_linear, ycbcr_to_rgb, ColorSpace,
+};
+use chapter_0::filter::{
+    bilateral_parallel_in_space, bilateral_sequential, bilateral_sequential_in_space,
+    bilateral_sequential_with, BorderMode, Options,
+};
+use chapter_0::generate;
+use chapter_0::image::{Image, Pixel};
+
+fn close(lhs: [f64; 3], rhs: [f64; 3], tolerance: f64) -> bool {
+    lhs.iter()
+        .zip(rhs.iter())
+        .all(|(lhs, rhs)| (lhs - rhs).abs() <= tolerance)
+}
+
+#[test]
+fn should_convert_srgb_to_linear_and_back() {
+    assert_eq!(srgb_to_linear(0.0), 0.0);
+    assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-12);
+    assert!((srgb_to_linear(0.5) - 0.214_041).abs() < 1e-6);
+
+    for index in 0..256 {
+        let value = index as f64 / 255.0;
+        assert!((linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn should_convert_rgb_to_lab_and_back() {
+    assert!(close(rgb_to_lab([1.0, 1.0, 1.0]), [100.0, 0.0, 0.0], 1e-3));
+    assert!(close(rgb_to_lab([0.0, 0.0, 0.0]), [0.0, 0.0, 0.0], 1e-9));
+    assert!(close(
+        rgb_to_lab([1.0, 0.0, 0.0]),
+        [53.24, 80.09, 67.20],
+        0.01
+    ));
+    assert!(close(
+        rgb_to_lab([0.0, 0.0, 1.0]),
+        [32.30, 79.19, -107.86],
+        0.01
+    ));
+
+    for &rgb in &[[0.2, 0.4, 0.6], [0.9, 0.05, 0.5], [0.01, 0.01, 0.02]] {
+        // The published matrices are only inverse of each other to 7 digits.
+        assert!(close(lab_to_rgb(rgb_to_lab(rgb)), rgb, 1e-5));
+    }
+}
+
+#[test]
+fn should_convert_rgb_to_ycbcr_and_back() {
+    assert!(close(rgb_to_ycbcr([1.0, 1.0, 1.0]), [1.0, 0.5, 0.5], 1e-9));
+    assert!(close(
+        rgb_to_ycbcr([1.0, 0.0, 0.0]),
+        [0.299, 0.331, 1.0],
+        1e-3
+    ));
+
+    for &rgb in &[[0.2, 0.4, 0.6], [0.9, 0.05, 0.5], [0.0, 1.0, 0.0]] {
+        assert!(close(ycbcr_to_rgb(rgb_to_ycbcr(rgb)), rgb, 1e-9));
+    }
+}
+
+#[test]
+fn should_convert_pixels_in_sample_range() {
+    let pixel = Pixel::<u16> {
+        r: 65535,
+        g: 65535,
+        b: 65535,
+    };
+
+    assert!(close(
+        ColorSpace::Lab.coordinates(&pixel),
+        [100.0, 0.0, 0.0],
+        1e-3
+    ));
+    assert!(close(
+        ColorSpace::YCbCr.coordinates(&pixel),
+        [65535.0, 32767.5, 32767.5],
+        1e-6
+    ));
+
+    let pixel = Pixel::<f32> {
+        r: 0.25,
+        g: 0.5,
+        b: 0.75,
+    };
+
+    for &space in &[
+        ColorSpace::Rgb,
+        ColorSpace::LinearRgb,
+        ColorSpace::Lab,
+        ColorSpace::YCbCr,
+    ] {
+        let restored: Pixel<f32> = space.pixel(space.coordinates(&pixel));
+
+        assert!((restored.r - pixel.r).abs() < 1e-5);
+        assert!((restored.g - pixel.g).abs() < 1e-5);
+        assert!((restored.b - pixel.b).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn should_restore_integer_pixels_exactly() {
+    for &space in &[
+        ColorSpace::Rgb,
+        ColorSpace::LinearRgb,
+        ColorSpace::Lab,
+        ColorSpace::YCbCr,
+    ] {
+        for value in 0..256 {
+            let pixel = Pixel::<u8> {
+                r: value as u8,
+                g: (value * 7 % 256) as u8,
+                b: (255 - value) as u8,
+            };
+
+            assert_eq!(space.pixel::<u8>(space.coordinates(&pixel)), pixel);
+        }
+    }
+}
+
+#[test]
+fn should_match_plain_filter_in_rgb_512() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = bilateral_sequential(&image, 5, 3.5, 3.0);
+    let (space, options) = (ColorSpace::Rgb, Options::default());
+
+    let sequential_output = bilateral_sequential_in_space(&image, 5, 3.5, 3.0, space, options);
+    let parallel_output = bilateral_parallel_in_space(&image, 5, 3.5, 3.0, space, options);
+
+    assert!(sequential_output.pixels == reference_output.pixels);
+    assert!(parallel_output.pixels == reference_output.pixels);
+    assert_eq!(parallel_output.metadata, image.metadata);
+}
+
+#[test]
+fn should_filter_in_lab_512() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let (space, options) = (ColorSpace::Lab, Options::default());
+
+    let sequential_output = bilateral_sequential_in_space(&image, 5, 3.5, 2.0, space, options);
+    let parallel_output = bilateral_parallel_in_space(&image, 5, 3.5, 2.0, space, options);
+
+    assert!(sequential_output.pixels == parallel_output.pixels);
+}
+
+#[test]
+fn should_honour_border_modes_in_space() {
+    let image: Image = generate::photographic(40, 30, 1);
+
+    for &border in &[
+        BorderMode::Replicate,
+        BorderMode::Mirror,
+        BorderMode::Wrap,
+        BorderMode::Constant,
+    ] {
+        let options = Options {
+            border,
+            border_value: 200.0,
+            ..Options::default()
+        };
+        let reference_output = bilateral_sequential_with(&image, 4, 3.5, 30.0, options);
+
+        let sequential_output =
+            bilateral_sequential_in_space(&image, 4, 3.5, 30.0, ColorSpace::Rgb, options);
+        let parallel_output =
+            bilateral_parallel_in_space(&image, 4, 3.5, 30.0, ColorSpace::Rgb, options);
+
+        assert!(sequential_output.pixels == reference_output.pixels);
+        assert!(parallel_output.pixels == reference_output.pixels);
+    }
+}
+
+#[test]
+fn should_keep_edges_far_apart_in_lab() {
+    // Black and white are 100 apart, which outweighs any neighbour with a small `sigma_r`.
+    let mut image = Image::<Pixel>::new(16, 4);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        let value = if index % 16 < 8 { 0 } else { 255 };
+        *pixel = Pixel {
+            r: value,
+            g: value,
+            b: value,
+        };
+    }
+
+    let deviation = |output: &Image<Pixel>| {
+        output
+            .pixels
+            .iter()
+            .zip(image.pixels.iter())
+            .map(|(lhs, rhs)| (lhs.r as i32 - rhs.r as i32).abs())
+            .max()
+            .unwrap()
+    };
+
+    let (space, options) = (ColorSpace::Lab, Options::default());
+
+    // Truncation alone may take white one level down.
+    let output = bilateral_parallel_in_space(&image, 3, 3.5, 1.0, space, options);
+    assert!(deviation(&output) <= 1);
+
+    let output = bilateral_parallel_in_space(&image, 3, 3.5, 100.0, space, options);
+    assert!(deviation(&output) > 50);
+}
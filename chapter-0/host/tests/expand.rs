@@ -0,0 +1,135 @@
+extern crate chapter_0;
+extern crate png;
+
+use std::env::temp_dir;
+use std::fs::File;
+use std::io::BufWriter;
+
+use png::{BitDepth, ColorType, Encoder, HasParameters};
+
+use chapter_0::image::{Expansion, Image, ImageError, Luma, Pixel, Rgba};
+
+fn write_png(
+    name: &str,
+    color_type: ColorType,
+    bit_depth: BitDepth,
+    chunks: &[([u8; 4], &[u8])],
+    data: &[u8],
+) -> String {
+    let path = temp_dir().join(name);
+    let file = File::create(&path).unwrap();
+
+    let mut encoder = Encoder::new(BufWriter::new(file), 16, 16);
+    encoder.set(color_type).set(bit_depth);
+
+    let mut writer = encoder.write_header().unwrap();
+
+    for &(name, chunk) in chunks {
+        writer.write_chunk(name, chunk).unwrap();
+    }
+
+    writer.write_image_data(data).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn palette_png() -> String {
+    let palette = (0..16u8)
+        .flat_map(|index| vec![index * 16, 255 - index * 16, index])
+        .collect::<Vec<_>>();
+
+    let trns = (0..8u8).map(|index| index * 32).collect::<Vec<_>>();
+
+    // Two 4-bit indices per byte: every row goes through the whole palette twice.
+    let data = (0..16 * 8).map(|index| (((index * 2 % 16) << 4) | ((index * 2 + 1) % 16)) as u8);
+
+    write_png(
+        "chapter-0-expand-palette.png",
+        ColorType::Indexed,
+        BitDepth::Four,
+        &[(*b"PLTE", &palette), (*b"tRNS", &trns)],
+        &data.collect::<Vec<_>>(),
+    )
+}
+
+#[test]
+fn should_expand_palette_with_transparency() {
+    let image = Image::<Rgba>::open(&palette_png()).unwrap();
+
+    for (index, pixel) in image.pixels.iter().enumerate() {
+        let entry = (index % 16) as u8;
+        let alpha = if entry < 8 { entry * 32 } else { 255 };
+
+        assert_eq!(
+            (pixel.r, pixel.g, pixel.b, pixel.a),
+            (entry * 16, 255 - entry * 16, entry, alpha)
+        );
+    }
+}
+
+#[test]
+fn should_expand_palette_without_alpha() {
+    let image = Image::<Pixel>::open(&palette_png()).unwrap();
+
+    for (index, pixel) in image.pixels.iter().enumerate() {
+        let entry = (index % 16) as u8;
+
+        assert_eq!(
+            (pixel.r, pixel.g, pixel.b),
+            (entry * 16, 255 - entry * 16, entry)
+        );
+    }
+}
+
+#[test]
+fn should_expand_sub_byte_grayscale() {
+    // Four 2-bit samples per byte: 0, 1, 2, 3.
+    let path = write_png(
+        "chapter-0-expand-gray.png",
+        ColorType::Grayscale,
+        BitDepth::Two,
+        &[],
+        &[0b00011011; 16 * 4],
+    );
+
+    let image = Image::<Luma>::open(&path).unwrap();
+
+    for (index, pixel) in image.pixels.iter().enumerate() {
+        assert_eq!(pixel.y, [0, 85, 170, 255][index % 4]);
+    }
+}
+
+#[test]
+fn should_expand_color_key() {
+    let data = (0..16 * 16 * 3).map(|index| (index / 3 % 2 * 255) as u8);
+
+    let path = write_png(
+        "chapter-0-expand-key.png",
+        ColorType::RGB,
+        BitDepth::Eight,
+        &[(*b"tRNS", &[0, 255, 0, 255, 0, 255])],
+        &data.collect::<Vec<_>>(),
+    );
+
+    let image = Image::<Rgba>::open(&path).unwrap();
+
+    for (index, pixel) in image.pixels.iter().enumerate() {
+        assert_eq!(pixel.a, if index % 2 == 0 { 255 } else { 0 });
+    }
+}
+
+#[test]
+fn should_stay_strict() {
+    let result = Image::<Rgba>::open_with(&palette_png(), Expansion::Strict);
+
+    match result {
+        Err(ImageError::UnsupportedColorType(ColorType::Indexed)) => {}
+        _ => panic!("Indexed image must be rejected"),
+    }
+
+    let result = Image::<Pixel<u16>>::open_with("../../fixtures/input-512.png", Expansion::Allow);
+
+    match result {
+        Err(ImageError::UnsupportedBitDepth(BitDepth::Eight)) => {}
+        _ => panic!("8-bit image must be rejected"),
+    }
+}
@@ -0,0 +1,111 @@
+extern crate chapter_0;
+
+use std::f64;
+
+use chapter_0::filter::{
+    bilateral_fast, bilateral_fast_max_deviation, bilateral_fast_with, bilateral_sequential,
+    bilateral_sequential_with, AlphaMode, BorderMode, Options, WindowMode,
+};
+use chapter_0::generate;
+use chapter_0::image::{Image, Luma, Pixel, Rgba};
+use chapter_0::metrics::max_abs_error;
+
+#[test]
+fn should_stay_within_max_deviation() {
+    let image: Image<Pixel<f32>> = generate::photographic(64, 48, 1);
+
+    for &(radius, sigma_d, sigma_r) in &[(5, 3.5, 0.1), (3, 1.5, 0.02), (8, 4.0, 1.0)] {
+        let fast = bilateral_fast(&image, radius, sigma_d, sigma_r);
+        let exact = bilateral_sequential(&image, radius, sigma_d, sigma_r);
+
+        let error = max_abs_error(&fast, &exact);
+        let bound = bilateral_fast_max_deviation(radius);
+
+        assert!(error <= bound, "error {} above {}", error, bound);
+    }
+}
+
+#[test]
+fn should_be_one_step_from_reference() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output: Image = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    let fast = bilateral_fast(&input, 5, 3.5, 3.0);
+
+    assert!(max_abs_error(&fast, &reference_output) <= 1.0);
+
+    let differing = fast
+        .pixels
+        .iter()
+        .zip(&reference_output.pixels)
+        .filter(|&(lhs, rhs)| lhs != rhs)
+        .count();
+
+    assert!(
+        differing * 100 < fast.pixels.len(),
+        "{} pixels differ",
+        differing
+    );
+}
+
+#[test]
+fn should_honour_options() {
+    let colors: Image<Pixel<f32>> = generate::photographic(40, 32, 2);
+    let mut image = Image::<Rgba<f32>>::new(40, 32);
+
+    for (index, (target, pixel)) in image.pixels.iter_mut().zip(&colors.pixels).enumerate() {
+        *target = Rgba {
+            r: pixel.r,
+            g: pixel.g,
+            b: pixel.b,
+            a: (index % 7) as f32 / 6.0,
+        };
+    }
+
+    for &border in &[BorderMode::Shrink, BorderMode::Mirror, BorderMode::Constant] {
+        let options = Options {
+            alpha: AlphaMode::Preserve,
+            border,
+            border_value: 0.5,
+            window: WindowMode::Symmetric,
+            ..Options::default()
+        };
+
+        let fast = bilateral_fast_with(&image, 4, 2.0, 0.1, options);
+        let exact = bilateral_sequential_with(&image, 4, 2.0, 0.1, options);
+
+        assert!(max_abs_error(&fast, &exact) <= bilateral_fast_max_deviation(4));
+        assert!(fast
+            .pixels
+            .iter()
+            .zip(&image.pixels)
+            .all(|(filtered, source)| filtered.a == source.a));
+    }
+}
+
+#[test]
+fn should_match_box_filter_exactly() {
+    let colors: Image<Pixel<f32>> = generate::photographic(24, 16, 3);
+    let mut image = Image::<Luma<f32>>::new(24, 16);
+
+    for (target, pixel) in image.pixels.iter_mut().zip(&colors.pixels) {
+        target.y = pixel.g;
+    }
+    let options = Options {
+        border: BorderMode::Wrap,
+        window: WindowMode::Symmetric,
+        ..Options::default()
+    };
+
+    let fast = bilateral_fast_with(&image, 2, f64::INFINITY, f64::INFINITY, options);
+    let exact = bilateral_sequential_with(&image, 2, f64::INFINITY, f64::INFINITY, options);
+
+    assert_eq!(fast.pixels, exact.pixels);
+}
+
+#[test]
+fn should_bound_deviation_below_an_8_bit_step() {
+    assert!(bilateral_fast_max_deviation(1) < bilateral_fast_max_deviation(2));
+    assert!(bilateral_fast_max_deviation(5) * 255.0 < 0.1);
+    assert!(bilateral_fast_max_deviation(21) * 255.0 < 1.0);
+}
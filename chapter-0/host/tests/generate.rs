@@ -0,0 +1,159 @@
+extern crate chapter_0;
+
+use chapter_0::filter::{bilateral_parallel, bilateral_sequential};
+use chapter_0::generate::{self, Random};
+use chapter_0::image::{Image, Pixel};
+
+#[test]
+fn should_generate_reference_sequence() {
+    let mut random = Random::new(0);
+
+    assert_eq!(random.next_u64(), 0xe220_a839_7b1d_cdaf);
+    assert_eq!(random.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+
+    for _ in 0..1000 {
+        let value = random.next_f64();
+        assert!((0.0..1.0).contains(&value));
+    }
+}
+
+#[test]
+fn should_be_deterministic() {
+    let first: Image = generate::photographic(64, 48, 7);
+    let second: Image = generate::photographic(64, 48, 7);
+    let other: Image = generate::photographic(64, 48, 8);
+
+    assert_eq!(first.pixels, second.pixels);
+    assert_ne!(first.pixels, other.pixels);
+
+    let first: Image = generate::noise(64, 48, 7);
+    let second: Image = generate::noise(64, 48, 7);
+
+    assert_eq!(first.pixels, second.pixels);
+}
+
+#[test]
+fn should_generate_any_size() {
+    let images: Vec<Image> = vec![
+        generate::gradient(37, 19),
+        generate::checkerboard(37, 19, 4),
+        generate::step_edge(37, 19),
+        generate::noise(37, 19, 1),
+        generate::photographic(37, 19, 1),
+        generate::photographic(1, 1, 1),
+    ];
+
+    for image in &images[..5] {
+        assert_eq!((image.width, image.height), (37, 19));
+        assert_eq!(image.pixels.len(), 37 * 19);
+    }
+
+    assert_eq!(images[5].pixels.len(), 1);
+}
+
+#[test]
+fn should_generate_gradient() {
+    let image: Image = generate::gradient(256, 256);
+
+    assert_eq!(image.pixels[0], Pixel { r: 0, g: 0, b: 0 });
+    assert_eq!(
+        image.pixels[255],
+        Pixel {
+            r: 255,
+            g: 0,
+            b: 127
+        }
+    );
+    assert_eq!(
+        image.pixels[255 * 256],
+        Pixel {
+            r: 0,
+            g: 255,
+            b: 127
+        }
+    );
+    assert_eq!(
+        image.pixels[256 * 256 - 1],
+        Pixel {
+            r: 255,
+            g: 255,
+            b: 255
+        }
+    );
+}
+
+#[test]
+fn should_generate_checkerboard() {
+    let image: Image = generate::checkerboard(16, 16, 4);
+    let white = Pixel {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+    let black = Pixel { r: 0, g: 0, b: 0 };
+
+    assert_eq!(image.pixels[0], white);
+    assert_eq!(image.pixels[3 * 16 + 3], white);
+    assert_eq!(image.pixels[4], black);
+    assert_eq!(image.pixels[4 * 16], black);
+    assert_eq!(image.pixels[4 * 16 + 4], white);
+    assert_eq!(image.pixels[15 * 16 + 15], white);
+}
+
+#[test]
+#[should_panic(expected = "checkerboard cells must be at least one pixel wide")]
+fn should_reject_empty_checkerboard_cells() {
+    let _: Image = generate::checkerboard(16, 16, 0);
+}
+
+#[test]
+fn should_generate_step_edge() {
+    let image: Image = generate::step_edge(10, 3);
+
+    for (index, pixel) in image.pixels.iter().enumerate() {
+        let expected = if index % 10 < 5 { 51 } else { 204 };
+        assert_eq!(pixel.r, expected);
+        assert_eq!(pixel.g, expected);
+        assert_eq!(pixel.b, expected);
+    }
+}
+
+#[test]
+fn should_generate_photographic_detail() {
+    let image: Image = generate::photographic(128, 128, 3);
+
+    let count = image.pixels.len() as f64;
+    let mean = image.pixels.iter().map(|pixel| pixel.g as f64).sum::<f64>() / count;
+    let variance = image
+        .pixels
+        .iter()
+        .map(|pixel| (pixel.g as f64 - mean) * (pixel.g as f64 - mean))
+        .sum::<f64>()
+        / count;
+
+    assert!(variance > 100.0, "variance {}", variance);
+}
+
+#[test]
+fn should_generate_deep_samples() {
+    let deep: Image<Pixel<u16>> = generate::photographic(32, 32, 5);
+    let float: Image<Pixel<f32>> = generate::photographic(32, 32, 5);
+
+    assert!(deep.pixels.iter().any(|pixel| pixel.r > 255));
+
+    for pixel in &float.pixels {
+        assert!(pixel.r >= 0.0 && pixel.r <= 1.0);
+        assert!(pixel.g >= 0.0 && pixel.g <= 1.0);
+        assert!(pixel.b >= 0.0 && pixel.b <= 1.0);
+    }
+}
+
+#[test]
+fn should_filter_generated_images() {
+    let image: Image = generate::photographic(97, 61, 11);
+
+    let sequential = bilateral_sequential(&image, 5, 3.5, 3.0);
+    let parallel = bilateral_parallel(&image, 5, 3.5, 3.0);
+
+    assert_eq!(sequential.pixels, parallel.pixels);
+}
@@ -0,0 +1,127 @@
+extern crate chapter_0;
+
+use chapter_0::filter::{
+    bilateral_parallel_luma, bilateral_parallel_with, bilateral_sequential_with, AlphaMode, Options,
+};
+use chapter_0::image::{GenericPixel, Image, Luma, Pixel, Rgba};
+
+// A format the crate knows nothing about: gray with alpha.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct LumaAlpha {
+    y: u16,
+    a: u16,
+}
+
+impl GenericPixel for LumaAlpha {
+    type Sample = u16;
+
+    const CHANNELS: usize = 2;
+    const COLOR_CHANNELS: usize = 1;
+
+    fn channel(&self, index: usize) -> u16 {
+        if index == 0 {
+            self.y
+        } else {
+            self.a
+        }
+    }
+
+    fn set_channel(&mut self, index: usize, value: u16) {
+        if index == 0 {
+            self.y = value;
+        } else {
+            self.a = value;
+        }
+    }
+}
+
+fn options(alpha: AlphaMode) -> Options {
+    Options {
+        alpha,
+        ..Options::default()
+    }
+}
+
+#[test]
+fn should_produce_reference_image_512() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output: Image = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    let sequential_output =
+        bilateral_sequential_with(&image, 5, 3.5, 3.0, options(AlphaMode::Filter));
+    let parallel_output = bilateral_parallel_with(&image, 5, 3.5, 3.0, options(AlphaMode::Filter));
+
+    assert!(sequential_output.pixels == reference_output.pixels);
+    assert!(parallel_output.pixels == reference_output.pixels);
+}
+
+#[test]
+fn should_filter_color_of_rgba_like_rgb() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output: Image = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    let mut rgba = Image::<Rgba>::new(image.width, image.height);
+
+    for (index, (target, pixel)) in rgba.pixels.iter_mut().zip(image.pixels.iter()).enumerate() {
+        *target = Rgba {
+            r: pixel.r,
+            g: pixel.g,
+            b: pixel.b,
+            a: (index % 256) as u8,
+        };
+    }
+
+    let output = bilateral_parallel_with(&rgba, 5, 3.5, 3.0, options(AlphaMode::Preserve));
+
+    for ((lhs, rhs), source) in output
+        .pixels
+        .iter()
+        .zip(reference_output.pixels.iter())
+        .zip(rgba.pixels.iter())
+    {
+        assert_eq!((lhs.r, lhs.g, lhs.b), (rhs.r, rhs.g, rhs.b));
+        assert_eq!(lhs.a, source.a);
+    }
+}
+
+#[test]
+fn should_filter_custom_pixel_format() {
+    let mut image = Image::<LumaAlpha>::new(48, 40);
+    let mut luma = Image::<Luma<u16>>::new(48, 40);
+
+    for (index, (pixel, y)) in image
+        .pixels
+        .iter_mut()
+        .zip(luma.pixels.iter_mut())
+        .enumerate()
+    {
+        y.y = (index * 7919 % 65536) as u16;
+        *pixel = LumaAlpha {
+            y: y.y,
+            a: (index * 31) as u16,
+        };
+    }
+
+    let reference_output = bilateral_parallel_luma(&luma, 3, 3.5, 3000.0);
+
+    for &alpha in &[AlphaMode::Preserve, AlphaMode::Filter] {
+        let sequential_output = bilateral_sequential_with(&image, 3, 3.5, 3000.0, options(alpha));
+        let parallel_output = bilateral_parallel_with(&image, 3, 3.5, 3000.0, options(alpha));
+
+        assert!(sequential_output.pixels == parallel_output.pixels);
+
+        for (index, pixel) in parallel_output.pixels.iter().enumerate() {
+            assert_eq!(pixel.y, reference_output.pixels[index].y);
+
+            if alpha == AlphaMode::Preserve {
+                assert_eq!(pixel.a, image.pixels[index].a);
+            }
+        }
+    }
+
+    let float = Image::<Pixel<f32>>::new(8, 8);
+    assert!(
+        bilateral_parallel_with(&float, 2, 3.5, 0.1, options(AlphaMode::Filter)).pixels
+            == float.pixels
+    );
+}
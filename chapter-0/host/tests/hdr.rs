@@ -0,0 +1,179 @@
+extern crate chapter_0;
+
+use std::env::temp_dir;
+
+use chapter_0::filter::{bilateral_parallel, bilateral_sequential};
+use chapter_0::image::{Format, Image, ImageError, Pixel};
+
+fn to_hdr(source: &Image, scale: f32) -> Image<Pixel<f32>> {
+    let mut image = Image::new(source.width, source.height);
+
+    for (index, pixel) in source.pixels.iter().enumerate() {
+        image.pixels[index] = Pixel {
+            r: pixel.r as f32 * scale,
+            g: pixel.g as f32 * scale,
+            b: pixel.b as f32 * scale,
+        };
+    }
+
+    image
+}
+
+fn gradient(width: usize, height: usize) -> Image<Pixel<f32>> {
+    let mut image = Image::new(width, height);
+
+    for i in 0..height {
+        for j in 0..width {
+            // Flat spans give the run length encoder something to do.
+            image.pixels[i * width + j] = Pixel {
+                r: (j / 9) as f32 * 0.37,
+                g: 1000.0 / (1 + i * j) as f32,
+                b: if (i + j) % 5 == 0 {
+                    0.0
+                } else {
+                    1e-3 * j as f32
+                },
+            };
+        }
+    }
+
+    image
+}
+
+fn close(lhs: f32, rhs: f32, tolerance: f32) -> bool {
+    (lhs - rhs).abs() <= tolerance * lhs.abs().max(rhs.abs())
+}
+
+#[test]
+fn should_save_and_open_pfm() {
+    let mut image = gradient(33, 17);
+    image.pixels[5].r = -2.5;
+
+    let path = temp_dir().join("chapter-0-hdr.pfm");
+    image.save_hdr(path.to_str().unwrap()).unwrap();
+
+    let reopened = Image::open_hdr(path.to_str().unwrap()).unwrap();
+
+    assert_eq!((reopened.width, reopened.height), (33, 17));
+    assert!(reopened.pixels == image.pixels);
+}
+
+#[test]
+fn should_save_and_open_radiance() {
+    // Narrow images are stored flat, wider ones run length encoded.
+    for &width in &[5, 300] {
+        let image = gradient(width, 12);
+
+        let path = temp_dir().join(format!("chapter-0-hdr-{}.hdr", width));
+        image.save_hdr(path.to_str().unwrap()).unwrap();
+
+        let reopened = Image::open_hdr(path.to_str().unwrap()).unwrap();
+
+        assert_eq!((reopened.width, reopened.height), (width, 12));
+
+        for (lhs, rhs) in reopened.pixels.iter().zip(image.pixels.iter()) {
+            // Channels share the exponent of the largest one, which keeps 8 bits of mantissa.
+            let largest = rhs.r.max(rhs.g).max(rhs.b);
+
+            for &(lhs, rhs) in &[(lhs.r, rhs.r), (lhs.g, rhs.g), (lhs.b, rhs.b)] {
+                assert!((lhs - rhs).abs() <= largest / 128.0);
+            }
+        }
+    }
+}
+
+#[test]
+fn should_decode_radiance_bytes() {
+    let mut bytes = b"#?RGBE\nEXPOSURE=1.0\n\n-Y 1 +X 2\n".to_vec();
+    bytes.extend_from_slice(&[128, 64, 0, 129, 0, 0, 0, 0]);
+
+    let image = Image::read_hdr(&bytes[..]).unwrap();
+
+    let samples = |pixel: &Pixel<f32>| (pixel.r, pixel.g, pixel.b);
+
+    assert_eq!(samples(&image.pixels[0]), (1.0, 0.5, 0.0));
+    assert_eq!(samples(&image.pixels[1]), (0.0, 0.0, 0.0));
+}
+
+#[test]
+fn should_decode_big_endian_pfm() {
+    let mut bytes = b"PF\n1 2\n1.0\n".to_vec();
+
+    // Bottom row first.
+    for sample in &[0.25f32, 0.5, 0.75, 1.0, 2.0, 4.0] {
+        let bits = sample.to_bits();
+        bytes.extend_from_slice(&[
+            (bits >> 24) as u8,
+            (bits >> 16) as u8,
+            (bits >> 8) as u8,
+            bits as u8,
+        ]);
+    }
+
+    let image = Image::read_hdr(&bytes[..]).unwrap();
+
+    let samples = |pixel: &Pixel<f32>| (pixel.r, pixel.g, pixel.b);
+
+    assert_eq!(samples(&image.pixels[0]), (1.0, 2.0, 4.0));
+    assert_eq!(samples(&image.pixels[1]), (0.25, 0.5, 0.75));
+}
+
+#[test]
+fn should_reject_mismatched_formats() {
+    let mut bytes = Vec::new();
+    gradient(8, 8).write_hdr(&mut bytes, Format::Pfm).unwrap();
+
+    match Image::<Pixel>::from_bytes(&bytes) {
+        Err(ImageError::UnsupportedFormat) => {}
+        result => panic!("unexpected result: {:?}", result.map(|_| ())),
+    }
+
+    match Image::read_hdr(&b"Pf\n1 1\n-1.0\n\0\0\0\0"[..]) {
+        Err(ImageError::UnsupportedColorType(_)) => {}
+        result => panic!("unexpected result: {:?}", result.map(|_| ())),
+    }
+
+    match Image::read_hdr(&b"#?RADIANCE\n\n-Y 1 +X 8\n\x02\x02\x00\x09"[..]) {
+        Err(ImageError::InvalidData) => {}
+        result => panic!("unexpected result: {:?}", result.map(|_| ())),
+    }
+}
+
+#[test]
+fn should_match_8_bit_reference_512() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let image = to_hdr(&input, 1.0);
+
+    let reference_output: Image = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    let sequential_output = bilateral_sequential(&image, 5, 3.5, 3.0);
+    let parallel_output = bilateral_parallel(&image, 5, 3.5, 3.0);
+
+    assert!(sequential_output.pixels == parallel_output.pixels);
+
+    // The 8-bit filter truncates what is kept in full here.
+    let below = |lhs: f32, rhs: u8| lhs >= rhs as f32 - 1e-3 && lhs < rhs as f32 + 1.0 + 1e-3;
+
+    for (lhs, rhs) in sequential_output
+        .pixels
+        .iter()
+        .zip(reference_output.pixels.iter())
+    {
+        assert!(below(lhs.r, rhs.r) && below(lhs.g, rhs.g) && below(lhs.b, rhs.b));
+    }
+}
+
+#[test]
+fn should_filter_high_dynamic_range() {
+    // Range distances grow 100 times, and `sigma_r^2` with them, so the output just scales.
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+
+    let output = bilateral_parallel(&to_hdr(&input, 1.0), 5, 3.5, 3.0);
+    let scaled_output = bilateral_parallel(&to_hdr(&input, 100.0), 5, 3.5, 30.0);
+
+    for (lhs, rhs) in scaled_output.pixels.iter().zip(output.pixels.iter()) {
+        assert!(close(lhs.r, rhs.r * 100.0, 1e-5));
+        assert!(close(lhs.g, rhs.g * 100.0, 1e-5));
+        assert!(close(lhs.b, rhs.b * 100.0, 1e-5));
+    }
+}
@@ -0,0 +1,77 @@
+extern crate chapter_0;
+
+use std::env::temp_dir;
+
+use chapter_0::filter::{bilateral_parallel_luma, bilateral_sequential_luma};
+use chapter_0::image::{Image, Luma};
+
+fn to_luma(source: &Image) -> Image<Luma> {
+    let mut image = Image::new(source.width, source.height);
+
+    for (index, pixel) in source.pixels.iter().enumerate() {
+        let y = (pixel.r as u32 * 299 + pixel.g as u32 * 587 + pixel.b as u32 * 114) / 1000;
+        image.pixels[index] = Luma { y: y as u8 };
+    }
+
+    image
+}
+
+fn step_edge(width: usize, height: usize, low: u8, high: u8) -> Image<Luma> {
+    let mut image = Image::<Luma>::new(width, height);
+
+    for i in 0..height {
+        for j in 0..width {
+            image.pixels[i * width + j].y = if j < width / 2 { low } else { high };
+        }
+    }
+
+    image
+}
+
+#[test]
+fn should_save_and_open_luma() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let image = to_luma(&input);
+
+    let path = temp_dir().join("chapter-0-luma-512.png");
+    image.save(path.to_str().unwrap()).unwrap();
+
+    let reopened = Image::<Luma>::open(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(reopened.width, image.width);
+    assert_eq!(reopened.height, image.height);
+    assert!(reopened.pixels == image.pixels);
+}
+
+#[test]
+fn should_agree_between_backends_512() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let image = to_luma(&input);
+
+    let sequential_output = bilateral_sequential_luma(&image, 5, 3.5, 3.0);
+    let parallel_output = bilateral_parallel_luma(&image, 5, 3.5, 3.0);
+
+    assert!(sequential_output.pixels == parallel_output.pixels);
+}
+
+#[test]
+fn should_preserve_step_edge() {
+    let image = step_edge(64, 64, 0, 200);
+
+    for output in &[
+        bilateral_sequential_luma(&image, 5, 3.5, 3.0),
+        bilateral_parallel_luma(&image, 5, 3.5, 3.0),
+    ] {
+        for i in 0..image.height {
+            for j in 0..image.width {
+                let y = output.pixels[i * image.width + j].y;
+
+                if j < image.width / 2 {
+                    assert_eq!(y, 0);
+                } else {
+                    assert!(y >= 199);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+extern crate chapter_0;
+
+use std::env::temp_dir;
+
+use chapter_0::filter::{bilateral_parallel, bilateral_sequential};
+use chapter_0::image::{Format, IccProfile, Image, Metadata, PhysicalDimensions, Pixel};
+
+fn metadata() -> Metadata {
+    Metadata {
+        gamma: Some(45455),
+        srgb_intent: Some(1),
+        icc_profile: Some(IccProfile {
+            name: "Display P3".to_string(),
+            compressed: vec![0x78, 0x9c, 0x03, 0x00, 0x00, 0x00, 0x00, 0x01],
+        }),
+        physical_dimensions: Some(PhysicalDimensions {
+            x_pixels_per_unit: 2835,
+            y_pixels_per_unit: 5670,
+            meter: true,
+        }),
+        text: vec![
+            ("Title".to_string(), "Bilateral".to_string()),
+            ("Author".to_string(), "Ångström".to_string()),
+        ],
+    }
+}
+
+#[test]
+fn should_keep_metadata_through_filters() {
+    let mut image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    image.metadata = metadata();
+
+    assert!(bilateral_sequential(&image, 5, 3.5, 3.0).metadata == image.metadata);
+    assert!(bilateral_parallel(&image, 5, 3.5, 3.0).metadata == image.metadata);
+}
+
+#[test]
+fn should_save_and_open_metadata() {
+    let mut image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    image.metadata = metadata();
+
+    let output = bilateral_parallel(&image, 5, 3.5, 3.0);
+
+    let path = temp_dir().join("chapter-0-metadata-512.png");
+    output.save(path.to_str().unwrap()).unwrap();
+
+    let reopened: Image = Image::open(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(reopened.metadata, metadata());
+    assert!(reopened.pixels == output.pixels);
+}
+
+#[test]
+fn should_read_fixture_metadata() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+
+    let dimensions = image.metadata.physical_dimensions.unwrap();
+    let profile = image.metadata.icc_profile.clone().unwrap();
+
+    assert_eq!(image.metadata.gamma, Some(45455));
+    assert_eq!(
+        (dimensions.x_pixels_per_unit, dimensions.meter),
+        (3937, true)
+    );
+    assert_eq!(profile.name, "ICC profile");
+
+    let reopened: Image = Image::from_bytes(&image.to_bytes(Format::Png).unwrap()).unwrap();
+
+    assert_eq!(reopened.metadata, image.metadata);
+    assert_eq!(Image::<Pixel>::new(4, 4).metadata, Metadata::default());
+}
@@ -0,0 +1,112 @@
+extern crate chapter_0;
+
+use chapter_0::filter::bilateral_parallel;
+use chapter_0::image::{Image, Luma, Pixel};
+use chapter_0::metrics::{channel_stats, diff_image, max_abs_error, mse, psnr, ssim};
+
+fn gradient(width: usize, height: usize) -> Image<Luma> {
+    let mut image = Image::<Luma>::new(width, height);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = ((index % width) * 200 / width) as u8;
+    }
+
+    image
+}
+
+#[test]
+fn should_measure_identical_images() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+
+    assert_eq!(mse(&image, &image), 0.0);
+    assert_eq!(psnr(&image, &image), f64::INFINITY);
+    assert_eq!(max_abs_error(&image, &image), 0.0);
+    assert!((ssim(&image, &image) - 1.0).abs() < 1e-12);
+
+    for stats in channel_stats(&image, &image) {
+        assert_eq!(stats.different, 0);
+    }
+}
+
+#[test]
+fn should_measure_constant_offset() {
+    let image = gradient(64, 32);
+    let mut shifted = image.as_view().to_image();
+
+    for pixel in &mut shifted.pixels {
+        pixel.y = pixel.y.saturating_add(10);
+    }
+
+    let stats = &channel_stats(&shifted, &image)[0];
+
+    assert_eq!(stats.mse, 100.0);
+    assert_eq!(stats.mean_error, 10.0);
+    assert_eq!(stats.max_abs_error, 10.0);
+    assert_eq!(stats.different, 64 * 32);
+
+    // 20 log10(255 / 10)
+    assert!((psnr(&shifted, &image) - 28.130_803_6).abs() < 1e-6);
+
+    // Structure is intact, only the luminance term drops.
+    let similarity = ssim(&shifted, &image);
+    assert!(similarity > 0.9 && similarity < 1.0);
+}
+
+#[test]
+fn should_measure_channels_apart() {
+    let mut image = Image::<Pixel<u16>>::new(4, 4);
+    let mut changed = image.as_view().to_image();
+
+    changed.pixels[5].g = 300;
+    changed.pixels[6].b = 65535;
+
+    let stats = channel_stats(&changed, &image);
+
+    assert_eq!(
+        stats
+            .iter()
+            .map(|stats| stats.different)
+            .collect::<Vec<_>>(),
+        vec![0, 1, 1]
+    );
+    assert_eq!(stats[1].mse, 300.0 * 300.0 / 16.0);
+    assert_eq!(max_abs_error(&changed, &image), 65535.0);
+
+    image.pixels[6].b = 65535;
+    assert_eq!(max_abs_error(&changed, &image), 300.0);
+}
+
+#[test]
+fn should_rank_filtered_images_by_ssim() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+
+    let light = bilateral_parallel(&image, 2, 1.0, 3.0);
+    let heavy = bilateral_parallel(&image, 5, 3.5, 30.0);
+
+    assert!(ssim(&light, &image) > ssim(&heavy, &image));
+    assert!(psnr(&light, &image) > psnr(&heavy, &image));
+}
+
+#[test]
+fn should_render_diff_heatmap() {
+    let image = gradient(3, 1);
+    let mut changed = image.as_view().to_image();
+
+    changed.pixels[1].y += 50;
+    changed.pixels[2].y -= 100;
+
+    let diff = diff_image(&changed, &image, 100.0);
+    let colors: Vec<_> = diff
+        .pixels
+        .iter()
+        .map(|pixel| (pixel.r, pixel.g, pixel.b))
+        .collect();
+
+    assert_eq!(colors, vec![(0, 0, 0), (255, 128, 0), (255, 255, 255)]);
+}
+
+#[test]
+#[should_panic]
+fn should_reject_images_of_different_size() {
+    mse(&gradient(4, 4), &gradient(4, 5));
+}
@@ -0,0 +1,208 @@
+extern crate chapter_0;
+
+use chapter_0::filter::bilateral_parallel;
+use chapter_0::generate;
+use chapter_0::image::{Image, Pixel, Rgba};
+use chapter_0::noise::{gaussian, poisson, recovery, salt_and_pepper};
+
+fn flat(width: usize, height: usize, value: f32) -> Image<Pixel<f32>> {
+    let mut image = Image::new(width, height);
+
+    for pixel in &mut image.pixels {
+        *pixel = Pixel {
+            r: value,
+            g: value,
+            b: value,
+        };
+    }
+
+    image
+}
+
+fn mean_and_variance(image: &Image<Pixel<f32>>) -> (f64, f64) {
+    let count = image.pixels.len() as f64;
+    let mean = image.pixels.iter().map(|pixel| pixel.r as f64).sum::<f64>() / count;
+    let variance = image
+        .pixels
+        .iter()
+        .map(|pixel| (pixel.r as f64 - mean) * (pixel.r as f64 - mean))
+        .sum::<f64>()
+        / count;
+
+    (mean, variance)
+}
+
+#[test]
+fn should_be_reproducible() {
+    let clean: Image = generate::photographic(64, 64, 1);
+
+    assert_eq!(
+        gaussian(&clean, 10.0, 3).pixels,
+        gaussian(&clean, 10.0, 3).pixels
+    );
+    assert_eq!(
+        poisson(&clean, 50.0, 3).pixels,
+        poisson(&clean, 50.0, 3).pixels
+    );
+    assert_eq!(
+        salt_and_pepper(&clean, 0.1, 3).pixels,
+        salt_and_pepper(&clean, 0.1, 3).pixels
+    );
+
+    assert_ne!(
+        gaussian(&clean, 10.0, 3).pixels,
+        gaussian(&clean, 10.0, 4).pixels
+    );
+}
+
+#[test]
+fn should_add_gaussian_noise() {
+    let noisy = gaussian(&flat(200, 200, 0.5), 0.05, 1);
+    let (mean, variance) = mean_and_variance(&noisy);
+
+    assert!((mean - 0.5).abs() < 0.002, "mean {}", mean);
+    assert!(
+        (variance.sqrt() - 0.05).abs() < 0.002,
+        "sigma {}",
+        variance.sqrt()
+    );
+}
+
+#[test]
+fn should_add_poisson_noise() {
+    // Variance of the counts equals their mean: 50 photons, then 500 on the normal path.
+    for &peak in &[100.0, 1000.0] {
+        let noisy = poisson(&flat(200, 200, 0.5), peak, 1);
+        let (mean, variance) = mean_and_variance(&noisy);
+        let expected = 0.5 / peak;
+
+        assert!((mean - 0.5).abs() < 0.005, "mean {}", mean);
+        assert!(
+            (variance - expected).abs() < 0.1 * expected,
+            "variance {}",
+            variance
+        );
+    }
+
+    let black = poisson(&flat(16, 16, 0.0), 100.0, 1);
+    assert!(black.pixels.iter().all(|pixel| pixel.r == 0.0));
+}
+
+#[test]
+fn should_keep_hdr_samples_above_white() {
+    for noisy in &[
+        gaussian(&flat(100, 100, 4.0), 0.1, 1),
+        poisson(&flat(100, 100, 4.0), 100.0, 1),
+    ] {
+        let (mean, _) = mean_and_variance(noisy);
+
+        assert!((mean - 4.0).abs() < 0.01, "mean {}", mean);
+        assert!(noisy.pixels.iter().all(|pixel| pixel.r > 1.0));
+    }
+
+    let dark = gaussian(&flat(100, 100, 0.0), 0.1, 1);
+    assert!(dark.pixels.iter().all(|pixel| pixel.r >= 0.0));
+}
+
+#[test]
+fn should_keep_mean_of_integer_samples() {
+    let mut clean: Image = Image::new(400, 400);
+
+    for pixel in &mut clean.pixels {
+        *pixel = Pixel {
+            r: 100,
+            g: 100,
+            b: 100,
+        };
+    }
+
+    for noisy in &[gaussian(&clean, 10.0, 1), poisson(&clean, 255.0, 1)] {
+        let count = 3.0 * noisy.pixels.len() as f64;
+        let sum = noisy
+            .pixels
+            .iter()
+            .map(|pixel| pixel.r as f64 + pixel.g as f64 + pixel.b as f64)
+            .sum::<f64>();
+        let mean = sum / count;
+
+        assert!((mean - 100.0).abs() < 0.05, "mean {}", mean);
+    }
+}
+
+#[test]
+fn should_add_salt_and_pepper_noise() {
+    let mut clean: Image = Image::new(100, 100);
+
+    for pixel in &mut clean.pixels {
+        *pixel = Pixel {
+            r: 128,
+            g: 128,
+            b: 128,
+        };
+    }
+
+    let noisy = salt_and_pepper(&clean, 0.1, 1);
+
+    let black = noisy
+        .pixels
+        .iter()
+        .filter(|pixel| **pixel == Pixel { r: 0, g: 0, b: 0 })
+        .count();
+    let white = noisy
+        .pixels
+        .iter()
+        .filter(|pixel| {
+            **pixel
+                == Pixel {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                }
+        })
+        .count();
+    let untouched = noisy.pixels.iter().filter(|pixel| pixel.r == 128).count();
+
+    assert_eq!(black + white + untouched, 100 * 100);
+    assert!(black > 400 && black < 600, "black {}", black);
+    assert!(white > 400 && white < 600, "white {}", white);
+}
+
+#[test]
+fn should_keep_alpha_and_metadata() {
+    let mut clean: Image<Rgba> = Image::new(32, 32);
+    clean.metadata.gamma = Some(45455);
+
+    for pixel in &mut clean.pixels {
+        *pixel = Rgba {
+            r: 100,
+            g: 100,
+            b: 100,
+            a: 77,
+        };
+    }
+
+    for noisy in &[
+        gaussian(&clean, 30.0, 1),
+        poisson(&clean, 10.0, 1),
+        salt_and_pepper(&clean, 0.5, 1),
+    ] {
+        assert!(noisy.pixels.iter().all(|pixel| pixel.a == 77));
+        assert!(noisy.pixels.iter().any(|pixel| pixel.r != 100));
+        assert_eq!(noisy.metadata, clean.metadata);
+    }
+}
+
+#[test]
+fn should_report_recovered_psnr() {
+    let clean: Image = generate::photographic(128, 128, 2);
+    let noisy = gaussian(&clean, 20.0, 2);
+
+    let filtered = recovery(&clean, &noisy, |image| {
+        bilateral_parallel(image, 3, 1.5, 30.0)
+    });
+    assert!(filtered.gain() > 5.0, "{:?}", filtered);
+
+    let untouched = recovery(&clean, &noisy, |image| image.as_view().to_image());
+    assert_eq!(untouched.gain(), 0.0);
+    assert_eq!(untouched.noisy_psnr, filtered.noisy_psnr);
+}
@@ -0,0 +1,140 @@
+extern crate chapter_0;
+
+use chapter_0::filter::{
+    bilateral_parallel_luma, bilateral_parallel_planar, bilateral_sequential,
+    bilateral_sequential_planar, bilateral_sequential_with, BorderMode, Options,
+};
+use chapter_0::generate;
+use chapter_0::image::{Image, Luma, Pixel, Rgba};
+use chapter_0::planar::PlanarImage;
+
+#[test]
+fn should_convert_to_planes_and_back() {
+    let mut image = Image::<Pixel<u16>>::new(5, 3);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        *pixel = Pixel {
+            r: index as u16,
+            g: 1000 + index as u16,
+            b: 60000 + index as u16,
+        };
+    }
+
+    let planar = PlanarImage::from_image(&image);
+
+    assert_eq!((planar.width, planar.height, planar.channels), (5, 3, 3));
+    assert_eq!(planar.plane(0)[7], 7);
+    assert_eq!(planar.plane(1)[7], 1007);
+    assert_eq!(planar.plane(2)[14], 60014);
+
+    let restored: Image<Pixel<u16>> = planar.to_image();
+
+    assert!(restored.pixels == image.pixels);
+}
+
+#[test]
+fn should_convert_luma_to_single_plane() {
+    let mut image = Image::<Luma>::new(4, 4);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = index as u8 * 3;
+    }
+
+    let planar = PlanarImage::from_image(&image);
+
+    assert_eq!(planar.channels, 1);
+    assert!(planar
+        .samples
+        .iter()
+        .eq(image.pixels.iter().map(|pixel| &pixel.y)));
+    assert!(planar.to_image::<Luma>().pixels == image.pixels);
+}
+
+#[test]
+#[should_panic]
+fn should_reject_mismatched_channel_count() {
+    let planar = PlanarImage::<u8>::new(4, 4, 1);
+    let _: Image<Pixel> = planar.to_image();
+}
+
+#[test]
+fn should_match_interleaved_filters_512() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = bilateral_sequential(&image, 5, 3.5, 3.0);
+    let planar = PlanarImage::from_image(&image);
+    let options = Options::default();
+
+    let sequential_output: Image =
+        bilateral_sequential_planar(&planar, 5, 3.5, 3.0, options).to_image();
+    let parallel_output: Image =
+        bilateral_parallel_planar(&planar, 5, 3.5, 3.0, options).to_image();
+
+    assert!(sequential_output.pixels == reference_output.pixels);
+    assert!(parallel_output.pixels == reference_output.pixels);
+    assert_eq!(parallel_output.metadata, image.metadata);
+}
+
+#[test]
+fn should_match_interleaved_luma_filter() {
+    let mut image = Image::<Luma>::new(64, 48);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = (index * 7 % 251) as u8;
+    }
+
+    let reference_output = bilateral_parallel_luma(&image, 3, 3.5, 30.0);
+    let planar = PlanarImage::from_image(&image);
+    let output = bilateral_parallel_planar(&planar, 3, 3.5, 30.0, Options::default());
+
+    assert!(output.to_image::<Luma>().pixels == reference_output.pixels);
+}
+
+#[test]
+fn should_honour_border_modes() {
+    let image: Image = generate::photographic(40, 30, 1);
+    let planar = PlanarImage::from_image(&image);
+
+    for &border in &[
+        BorderMode::Replicate,
+        BorderMode::Mirror,
+        BorderMode::Wrap,
+        BorderMode::Constant,
+    ] {
+        let options = Options {
+            border,
+            border_value: 200.0,
+            ..Options::default()
+        };
+        let reference_output = bilateral_sequential_with(&image, 4, 3.5, 30.0, options);
+
+        let sequential_output = bilateral_sequential_planar(&planar, 4, 3.5, 30.0, options);
+        let parallel_output = bilateral_parallel_planar(&planar, 4, 3.5, 30.0, options);
+
+        assert!(sequential_output.to_image::<Pixel>().pixels == reference_output.pixels);
+        assert!(parallel_output.to_image::<Pixel>().pixels == reference_output.pixels);
+    }
+}
+
+#[test]
+fn should_leave_alpha_out_of_range_distance() {
+    let colors: Image = generate::photographic(40, 30, 1);
+    let mut image = Image::<Rgba>::new(40, 30);
+
+    for (index, (target, pixel)) in image.pixels.iter_mut().zip(&colors.pixels).enumerate() {
+        *target = Rgba {
+            r: pixel.r,
+            g: pixel.g,
+            b: pixel.b,
+            a: (index * 37 % 256) as u8,
+        };
+    }
+
+    let reference_output = bilateral_sequential_with(&image, 4, 3.5, 30.0, Options::default());
+    let planar = PlanarImage::from_image(&image);
+
+    let sequential_output = bilateral_sequential_planar(&planar, 4, 3.5, 30.0, Options::default());
+    let parallel_output = bilateral_parallel_planar(&planar, 4, 3.5, 30.0, Options::default());
+
+    assert!(sequential_output.to_image::<Rgba>().pixels == reference_output.pixels);
+    assert!(parallel_output.to_image::<Rgba>().pixels == reference_output.pixels);
+}
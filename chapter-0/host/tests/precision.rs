@@ -0,0 +1,101 @@
+extern crate chapter_0;
+
+mod utils;
+
+use chapter_0::filter::{bilateral_parallel_with, bilateral_sequential_with, Options, Precision};
+use chapter_0::generate;
+use chapter_0::image::{Image, Pixel};
+use chapter_0::metrics::max_abs_error;
+
+use utils::compare_images;
+
+// Agreed bounds of `Precision::Single` against the f64 reference: one step for 8-bit samples,
+// in under 0.1% of the pixels, and `1e-5` for float samples.
+const MAX_STEPS: f64 = 1.0;
+const MAX_DIFFERING: f64 = 0.001;
+const MAX_FLOAT_ERROR: f64 = 1e-5;
+
+fn options(precision: Precision) -> Options {
+    Options {
+        precision,
+        ..Options::default()
+    }
+}
+
+fn check_against_reference(current_output: &Image, reference_output: &Image) {
+    let differing = current_output
+        .pixels
+        .iter()
+        .zip(&reference_output.pixels)
+        .filter(|&(lhs, rhs)| lhs != rhs)
+        .count();
+
+    assert!(max_abs_error(current_output, reference_output) <= MAX_STEPS);
+    assert!(
+        (differing as f64) < MAX_DIFFERING * current_output.pixels.len() as f64,
+        "{} pixels differ",
+        differing
+    );
+}
+
+#[test]
+fn should_keep_double_precision_by_default() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    assert_eq!(Options::default().precision, Precision::Double);
+    compare_images(
+        &bilateral_sequential_with(&input, 5, 3.5, 3.0, options(Precision::Double)),
+        &reference_output,
+    );
+}
+
+#[test]
+fn should_stay_close_to_reference_512() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    let sequential = bilateral_sequential_with(&input, 5, 3.5, 3.0, options(Precision::Single));
+    let parallel = bilateral_parallel_with(&input, 5, 3.5, 3.0, options(Precision::Single));
+
+    check_against_reference(&sequential, &reference_output);
+    assert_eq!(sequential.pixels, parallel.pixels);
+}
+
+#[test]
+fn should_stay_close_to_reference_1024() {
+    let input = Image::open("../../fixtures/input-1024.png").unwrap();
+    let reference_output = Image::open("../../fixtures/ref-output-1024.png").unwrap();
+
+    check_against_reference(
+        &bilateral_parallel_with(&input, 5, 3.5, 3.0, options(Precision::Single)),
+        &reference_output,
+    );
+}
+
+#[test]
+fn should_stay_close_with_float_samples() {
+    let image: Image<Pixel<f32>> = generate::photographic(96, 64, 1);
+
+    for &sigma_r in &[0.01, 0.1, 1.0] {
+        let single = bilateral_parallel_with(&image, 5, 3.5, sigma_r, options(Precision::Single));
+        let double = bilateral_parallel_with(&image, 5, 3.5, sigma_r, options(Precision::Double));
+
+        let error = max_abs_error(&single, &double);
+        assert!(error <= MAX_FLOAT_ERROR, "error {}", error);
+    }
+}
+
+#[test]
+fn should_stay_within_a_step_with_16_bit_samples() {
+    // `1e-5` of white is under a step of 16-bit samples, so truncation can add at most one.
+    let image: Image<Pixel<u16>> = generate::photographic(96, 64, 1);
+
+    for &sigma_r in &[300.0, 3000.0, 30000.0] {
+        let single = bilateral_parallel_with(&image, 5, 3.5, sigma_r, options(Precision::Single));
+        let double = bilateral_parallel_with(&image, 5, 3.5, sigma_r, options(Precision::Double));
+
+        let error = max_abs_error(&single, &double);
+        assert!(error <= MAX_STEPS, "error {}", error);
+    }
+}
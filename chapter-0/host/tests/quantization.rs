@@ -0,0 +1,194 @@
+extern crate chapter_0;
+
+use std::f64;
+
+use chapter_0::color::ColorSpace;
+use chapter_0::filter::{
+    bilateral_parallel_in_space, bilateral_parallel_planar, bilateral_parallel_with,
+    bilateral_sequential, bilateral_sequential_in_space, bilateral_sequential_planar,
+    bilateral_sequential_with, BorderMode, Options, Quantization, WindowMode,
+};
+use chapter_0::generate;
+use chapter_0::image::{Image, Luma, Pixel};
+use chapter_0::planar::PlanarImage;
+
+// Columns of 100, 100 and 102: every 3x3 window of the wrapped image averages to 100 + 2/3.
+fn stripes() -> Image<Luma> {
+    let mut image: Image<Luma> = Image::new(24, 24);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = if index % 3 == 2 { 102 } else { 100 };
+    }
+
+    image
+}
+
+// With infinite sigmas every weight is exactly 1, so the filter is a plain box average.
+fn box_options(quantization: Quantization, dither_seed: u32) -> Options {
+    Options {
+        border: BorderMode::Wrap,
+        window: WindowMode::Symmetric,
+        quantization,
+        dither_seed,
+        ..Options::default()
+    }
+}
+
+fn box_filter(quantization: Quantization, dither_seed: u32) -> Image<Luma> {
+    let options = box_options(quantization, dither_seed);
+
+    bilateral_sequential_with(&stripes(), 1, f64::INFINITY, f64::INFINITY, options)
+}
+
+fn mean<P, F: Fn(&P) -> f64>(image: &Image<P>, sample: F) -> f64 {
+    image.pixels.iter().map(sample).sum::<f64>() / image.pixels.len() as f64
+}
+
+#[test]
+fn should_truncate_by_default() {
+    assert_eq!(Options::default().quantization, Quantization::Truncate);
+    assert!(box_filter(Quantization::Truncate, 0)
+        .pixels
+        .iter()
+        .all(|pixel| pixel.y == 100));
+
+    let image: Image = generate::photographic(64, 48, 1);
+    let legacy = bilateral_sequential(&image, 5, 3.5, 30.0);
+    let default = bilateral_sequential_with(&image, 5, 3.5, 30.0, Options::default());
+
+    assert_eq!(legacy.pixels, default.pixels);
+}
+
+#[test]
+fn should_round_to_nearest() {
+    assert!(box_filter(Quantization::Round, 0)
+        .pixels
+        .iter()
+        .all(|pixel| pixel.y == 101));
+}
+
+#[test]
+fn should_dither_to_exact_mean() {
+    // 43 of the 64 thresholds are above 1/3, and the image is made of whole 8x8 tiles.
+    for seed in 0..64 {
+        let dithered = box_filter(Quantization::Dither, seed);
+
+        assert!(dithered
+            .pixels
+            .iter()
+            .all(|pixel| pixel.y == 100 || pixel.y == 101));
+        assert_eq!(mean(&dithered, |pixel| pixel.y as f64), 100.0 + 43.0 / 64.0);
+    }
+
+    assert_eq!(
+        box_filter(Quantization::Dither, 5).pixels,
+        box_filter(Quantization::Dither, 5).pixels
+    );
+    assert_ne!(
+        box_filter(Quantization::Dither, 5).pixels,
+        box_filter(Quantization::Dither, 6).pixels
+    );
+    assert_eq!(
+        box_filter(Quantization::Dither, 5).pixels,
+        box_filter(Quantization::Dither, 5 + 64).pixels
+    );
+}
+
+#[test]
+fn should_not_darken() {
+    let image: Image = generate::photographic(128, 96, 2);
+    let filter = |quantization| {
+        let options = Options {
+            quantization,
+            ..Options::default()
+        };
+
+        bilateral_sequential_with(&image, 5, 3.5, 30.0, options)
+    };
+
+    let truncated = mean(&filter(Quantization::Truncate), |pixel| pixel.g as f64);
+    let rounded = mean(&filter(Quantization::Round), |pixel| pixel.g as f64);
+    let dithered = mean(&filter(Quantization::Dither), |pixel| pixel.g as f64);
+
+    assert!(rounded - truncated > 0.45 && rounded - truncated < 0.55);
+    assert!((dithered - rounded).abs() < 0.05);
+}
+
+#[test]
+fn should_keep_float_samples() {
+    let image: Image<Pixel<f32>> = generate::photographic(64, 48, 3);
+    let filter = |quantization| {
+        let options = Options {
+            quantization,
+            ..Options::default()
+        };
+
+        bilateral_sequential_with(&image, 5, 3.5, 0.1, options)
+    };
+
+    let truncated = filter(Quantization::Truncate);
+
+    assert_eq!(truncated.pixels, filter(Quantization::Round).pixels);
+    assert_eq!(truncated.pixels, filter(Quantization::Dither).pixels);
+}
+
+#[test]
+fn should_quantize_identically_in_parallel() {
+    let image: Image = generate::photographic(97, 61, 4);
+
+    for &quantization in &[
+        Quantization::Truncate,
+        Quantization::Round,
+        Quantization::Dither,
+    ] {
+        let options = Options {
+            quantization,
+            dither_seed: 11,
+            ..Options::default()
+        };
+
+        let sequential = bilateral_sequential_with(&image, 5, 3.5, 30.0, options);
+        let parallel = bilateral_parallel_with(&image, 5, 3.5, 30.0, options);
+
+        assert_eq!(sequential.pixels, parallel.pixels);
+    }
+
+    let stripes = stripes();
+    let options = box_options(Quantization::Dither, 3);
+
+    assert_eq!(
+        bilateral_parallel_with(&stripes, 1, f64::INFINITY, f64::INFINITY, options).pixels,
+        box_filter(Quantization::Dither, 3).pixels
+    );
+}
+
+#[test]
+fn should_quantize_every_layout() {
+    let image: Image = generate::photographic(40, 32, 4);
+    let planar = PlanarImage::from_image(&image);
+    let truncated = bilateral_sequential_with(&image, 3, 3.5, 30.0, Options::default());
+
+    for &quantization in &[Quantization::Round, Quantization::Dither] {
+        let options = Options {
+            quantization,
+            dither_seed: 5,
+            ..Options::default()
+        };
+        let reference_output = bilateral_sequential_with(&image, 3, 3.5, 30.0, options);
+
+        assert!(reference_output.pixels != truncated.pixels);
+
+        for output in &[
+            bilateral_sequential_planar(&planar, 3, 3.5, 30.0, options).to_image(),
+            bilateral_parallel_planar(&planar, 3, 3.5, 30.0, options).to_image(),
+            bilateral_sequential_in_space(&image, 3, 3.5, 30.0, ColorSpace::Rgb, options),
+            bilateral_parallel_in_space(&image, 3, 3.5, 30.0, ColorSpace::Rgb, options),
+        ] {
+            assert!(
+                output.pixels == reference_output.pixels,
+                "{:?}",
+                quantization
+            );
+        }
+    }
+}
@@ -0,0 +1,202 @@
+extern crate chapter_0;
+
+use chapter_0::generate;
+use chapter_0::image::{Image, Luma, PhysicalDimensions, Rgba};
+use chapter_0::resize::{resize, resize_parallel, Interpolation};
+
+const KERNELS: [Interpolation; 3] = [
+    Interpolation::Bilinear,
+    Interpolation::Bicubic,
+    Interpolation::Lanczos3,
+];
+
+fn row(values: &[f32]) -> Image<Luma<f32>> {
+    let mut image = Image::<Luma<f32>>::new(values.len(), 1);
+
+    for (pixel, &value) in image.pixels.iter_mut().zip(values.iter()) {
+        pixel.y = value;
+    }
+
+    image
+}
+
+#[test]
+fn should_keep_image_at_same_size() {
+    let image: Image = generate::photographic(61, 37, 1);
+
+    for &kernel in &KERNELS {
+        assert_eq!(resize(&image, 61, 37, kernel).pixels, image.pixels);
+    }
+}
+
+#[test]
+fn should_keep_flat_images_flat() {
+    let mut image = Image::<Rgba<f32>>::new(13, 7);
+
+    for pixel in &mut image.pixels {
+        *pixel = Rgba {
+            r: 0.3,
+            g: 0.6,
+            b: 0.9,
+            a: 0.5,
+        };
+    }
+
+    for &kernel in &KERNELS {
+        for &(width, height) in &[(40, 21), (5, 3), (1, 1), (13, 30)] {
+            let resized = resize(&image, width, height, kernel);
+
+            assert_eq!((resized.width, resized.height), (width, height));
+            assert!(resized.pixels.iter().all(|pixel| *pixel == image.pixels[0]));
+        }
+    }
+}
+
+#[test]
+fn should_interpolate_linear_ramps() {
+    // Both bilinear and Catmull-Rom reproduce straight lines exactly, away from the borders.
+    let ramp: Vec<f32> = (0..16).map(|x| x as f32).collect();
+    let source = row(&ramp);
+
+    for &(kernel, margin) in &[(Interpolation::Bilinear, 2), (Interpolation::Bicubic, 6)] {
+        let resized = resize(&source, 64, 1, kernel);
+
+        for x in margin..64 - margin {
+            let expected = (x as f32 + 0.5) / 4.0 - 0.5;
+            assert!(
+                (resized.pixels[x].y - expected).abs() < 1e-5,
+                "{:?} at {}: {}",
+                kernel,
+                x,
+                resized.pixels[x].y
+            );
+        }
+    }
+}
+
+#[test]
+fn should_interpolate_smooth_signals() {
+    let period = 32.0;
+    let signal = |x: f64| (0.5 + 0.4 * (2.0 * std::f64::consts::PI * x / period).sin()) as f32;
+
+    let values: Vec<f32> = (0..128).map(|x| signal(x as f64)).collect();
+    let source = row(&values);
+
+    for &(kernel, tolerance) in &[
+        (Interpolation::Bilinear, 0.01),
+        (Interpolation::Bicubic, 0.002),
+        (Interpolation::Lanczos3, 0.002),
+    ] {
+        let resized = resize(&source, 384, 1, kernel);
+
+        for x in 12..372 {
+            let expected = signal((x as f64 + 0.5) / 3.0 - 0.5);
+            assert!(
+                (resized.pixels[x].y - expected).abs() < tolerance,
+                "{:?} at {}",
+                kernel,
+                x
+            );
+        }
+    }
+}
+
+#[test]
+fn should_average_detail_when_shrinking() {
+    let checkerboard: Image = generate::checkerboard(64, 64, 1);
+    let halved = resize(&checkerboard, 32, 32, Interpolation::Bilinear);
+
+    for i in 1..31 {
+        for j in 1..31 {
+            assert_eq!(halved.pixels[i * 32 + j].r, 128);
+        }
+    }
+
+    for &kernel in &KERNELS {
+        let quartered = resize(&checkerboard, 16, 16, kernel);
+
+        for pixel in &quartered.pixels {
+            assert!(
+                (pixel.r as f64 - 127.5).abs() <= 4.5,
+                "{:?}: {}",
+                kernel,
+                pixel.r
+            );
+        }
+    }
+}
+
+#[test]
+fn should_keep_mean_brightness() {
+    // Doubled, every value `x` of the ramp becomes `x - 0.25` and `x + 0.25`.
+    let mut ramp: Image<Luma> = Image::new(256, 4);
+
+    for (index, pixel) in ramp.pixels.iter_mut().enumerate() {
+        pixel.y = (index % 256) as u8;
+    }
+
+    let mean = |image: &Image<Luma>| {
+        image.pixels.iter().map(|pixel| pixel.y as f64).sum::<f64>() / image.pixels.len() as f64
+    };
+
+    for &(width, height) in &[(512, 4), (256, 8), (512, 8)] {
+        let resized = resize(&ramp, width, height, Interpolation::Bilinear);
+
+        assert_eq!(mean(&resized), 127.5, "{}x{}", width, height);
+    }
+}
+
+#[test]
+fn should_clip_overshoot_to_source_range() {
+    let step = row(&[0.2, 0.2, 0.2, 0.2, 0.8, 0.8, 0.8, 0.8]);
+
+    for &kernel in &KERNELS {
+        let resized = resize(&step, 50, 1, kernel);
+
+        for pixel in &resized.pixels {
+            assert!(
+                pixel.y >= 0.2 && pixel.y <= 0.8,
+                "{:?}: {}",
+                kernel,
+                pixel.y
+            );
+        }
+    }
+}
+
+#[test]
+fn should_resize_in_parallel() {
+    let image: Image = generate::photographic(97, 61, 4);
+
+    for &kernel in &KERNELS {
+        for &(width, height) in &[(200, 150), (31, 17), (97, 20)] {
+            assert_eq!(
+                resize(&image, width, height, kernel).pixels,
+                resize_parallel(&image, width, height, kernel).pixels
+            );
+        }
+    }
+}
+
+#[test]
+fn should_scale_physical_dimensions() {
+    let mut image: Image = generate::gradient(100, 50);
+    image.metadata.gamma = Some(45455);
+    image.metadata.physical_dimensions = Some(PhysicalDimensions {
+        x_pixels_per_unit: 2835,
+        y_pixels_per_unit: 2835,
+        meter: true,
+    });
+
+    let resized = resize_parallel(&image, 50, 100, Interpolation::Bicubic);
+
+    assert_eq!(resized.metadata.gamma, Some(45455));
+    assert_eq!(
+        resized.metadata.physical_dimensions,
+        Some(PhysicalDimensions {
+            x_pixels_per_unit: 1418,
+            y_pixels_per_unit: 5670,
+            meter: true,
+        })
+    );
+}
@@ -0,0 +1,178 @@
+extern crate chapter_0;
+
+use chapter_0::generate;
+use chapter_0::image::{Image, Luma, PhysicalDimensions};
+use chapter_0::transform::{
+    crop, flip_horizontal, flip_vertical, pad, rotate180, rotate270, rotate90, transpose, Padding,
+};
+
+// Pixels numbered in row-major order, so that every test can tell where they went.
+fn numbered(width: usize, height: usize) -> Image<Luma> {
+    let mut image = Image::<Luma>::new(width, height);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = index as u8;
+    }
+
+    image
+}
+
+fn values(image: &Image<Luma>) -> Vec<u8> {
+    image.pixels.iter().map(|pixel| pixel.y).collect()
+}
+
+#[test]
+fn should_crop() {
+    let image = numbered(4, 3);
+    let cropped = crop(&image, 1, 1, 2, 2);
+
+    assert_eq!((cropped.width, cropped.height), (2, 2));
+    assert_eq!(values(&cropped), vec![5, 6, 9, 10]);
+    assert_eq!(values(&crop(&image, 0, 0, 4, 3)), values(&image));
+}
+
+#[test]
+#[should_panic]
+fn should_not_crop_outside_of_image() {
+    crop(&numbered(4, 3), 3, 0, 2, 1);
+}
+
+#[test]
+fn should_pad_with_constant() {
+    let padded = pad(
+        &numbered(3, 2),
+        1,
+        0,
+        2,
+        1,
+        &Padding::Constant(Luma { y: 99 }),
+    );
+
+    assert_eq!((padded.width, padded.height), (6, 3));
+    assert_eq!(
+        values(&padded),
+        vec![99, 0, 1, 2, 99, 99, 99, 3, 4, 5, 99, 99, 99, 99, 99, 99, 99, 99]
+    );
+}
+
+#[test]
+fn should_pad_with_replicated_edges() {
+    let padded = pad(&numbered(3, 2), 2, 1, 1, 0, &Padding::Replicate);
+
+    assert_eq!((padded.width, padded.height), (6, 3));
+    assert_eq!(
+        values(&padded),
+        vec![0, 0, 0, 1, 2, 2, 0, 0, 0, 1, 2, 2, 3, 3, 3, 4, 5, 5]
+    );
+}
+
+#[test]
+fn should_pad_with_mirrored_edges() {
+    let padded = pad(&numbered(3, 1), 2, 0, 2, 0, &Padding::Mirror);
+    assert_eq!(values(&padded), vec![2, 1, 0, 1, 2, 1, 0]);
+
+    // Wider than the image, the reflection bounces back and forth.
+    let padded = pad(&numbered(3, 1), 5, 0, 0, 0, &Padding::Mirror);
+    assert_eq!(values(&padded), vec![1, 0, 1, 2, 1, 0, 1, 2]);
+
+    let padded = pad(&numbered(1, 2), 0, 3, 0, 0, &Padding::Mirror);
+    assert_eq!(values(&padded), vec![1, 0, 1, 0, 1]);
+
+    let padded = pad(&numbered(1, 1), 1, 1, 1, 1, &Padding::Mirror);
+    assert_eq!(values(&padded), vec![0; 9]);
+}
+
+#[test]
+fn should_pad_empty_images() {
+    let empty = Image::<Luma>::new(0, 0);
+
+    let padded = pad(&empty, 1, 1, 1, 0, &Padding::Constant(Luma { y: 7 }));
+    assert_eq!((padded.width, padded.height), (2, 1));
+    assert_eq!(values(&padded), vec![7, 7]);
+}
+
+#[test]
+fn should_flip() {
+    let image = numbered(3, 2);
+
+    assert_eq!(values(&flip_horizontal(&image)), vec![2, 1, 0, 5, 4, 3]);
+    assert_eq!(values(&flip_vertical(&image)), vec![3, 4, 5, 0, 1, 2]);
+}
+
+#[test]
+fn should_rotate() {
+    let image = numbered(3, 2);
+
+    let rotated = rotate90(&image);
+    assert_eq!((rotated.width, rotated.height), (2, 3));
+    assert_eq!(values(&rotated), vec![3, 0, 4, 1, 5, 2]);
+
+    let rotated = rotate180(&image);
+    assert_eq!((rotated.width, rotated.height), (3, 2));
+    assert_eq!(values(&rotated), vec![5, 4, 3, 2, 1, 0]);
+
+    let rotated = rotate270(&image);
+    assert_eq!((rotated.width, rotated.height), (2, 3));
+    assert_eq!(values(&rotated), vec![2, 5, 1, 4, 0, 3]);
+}
+
+#[test]
+fn should_transpose() {
+    let transposed = transpose(&numbered(3, 2));
+
+    assert_eq!((transposed.width, transposed.height), (2, 3));
+    assert_eq!(values(&transposed), vec![0, 3, 1, 4, 2, 5]);
+}
+
+#[test]
+fn should_compose() {
+    let image: Image = generate::photographic(23, 17, 3);
+
+    assert_eq!(rotate90(&rotate270(&image)).pixels, image.pixels);
+    assert_eq!(rotate90(&rotate90(&image)).pixels, rotate180(&image).pixels);
+    assert_eq!(transpose(&transpose(&image)).pixels, image.pixels);
+    assert_eq!(
+        flip_horizontal(&transpose(&image)).pixels,
+        rotate90(&image).pixels
+    );
+    assert_eq!(
+        flip_vertical(&flip_horizontal(&image)).pixels,
+        rotate180(&image).pixels
+    );
+
+    let padded = pad(&image, 3, 4, 5, 6, &Padding::Mirror);
+    assert_eq!(crop(&padded, 3, 4, 23, 17).pixels, image.pixels);
+}
+
+#[test]
+fn should_carry_metadata_over() {
+    let mut image = numbered(3, 2);
+    image.metadata.gamma = Some(45455);
+    image.metadata.physical_dimensions = Some(PhysicalDimensions {
+        x_pixels_per_unit: 100,
+        y_pixels_per_unit: 200,
+        meter: true,
+    });
+
+    for kept in &[
+        crop(&image, 1, 0, 2, 2),
+        pad(&image, 1, 1, 1, 1, &Padding::Replicate),
+        flip_horizontal(&image),
+        flip_vertical(&image),
+        rotate180(&image),
+    ] {
+        assert_eq!(kept.metadata, image.metadata);
+    }
+
+    for swapped in &[rotate90(&image), rotate270(&image), transpose(&image)] {
+        assert_eq!(swapped.metadata.gamma, Some(45455));
+        assert_eq!(
+            swapped.metadata.physical_dimensions,
+            Some(PhysicalDimensions {
+                x_pixels_per_unit: 200,
+                y_pixels_per_unit: 100,
+                meter: true,
+            })
+        );
+    }
+}
@@ -1,16 +1,21 @@
 use chapter_0::image::Image;
+use chapter_0::metrics::{channel_stats, max_abs_error, psnr};
 
 pub fn compare_images(current: &Image, reference: &Image) {
     assert_eq!(current.width, reference.width);
     assert_eq!(current.height, reference.height);
     assert_eq!(current.pixels.len(), reference.pixels.len());
 
-    let mut defferent_pixels_count = 0;
-    for index in 0..current.pixels.len() {
-        if current.pixels[index] != reference.pixels[index] {
-            defferent_pixels_count += 1;
-        }
-    }
+    let different: Vec<_> = channel_stats(current, reference)
+        .iter()
+        .map(|stats| stats.different)
+        .collect();
 
-    assert_eq!(defferent_pixels_count, 0);
+    assert!(
+        different.iter().all(|&count| count == 0),
+        "samples differ per channel: {:?}, PSNR {:.2} dB, max error {}",
+        different,
+        psnr(current, reference),
+        max_abs_error(current, reference)
+    );
 }
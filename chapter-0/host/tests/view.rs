@@ -0,0 +1,169 @@
+extern crate chapter_0;
+
+use chapter_0::filter::{
+    bilateral_parallel, bilateral_parallel_luma_view, bilateral_parallel_view,
+    bilateral_sequential, bilateral_sequential_view,
+};
+use chapter_0::image::{Image, Luma, Pixel};
+use chapter_0::view::{ImageView, ImageViewMut};
+
+fn inside(i: usize, j: usize, (x, y, width, height): (usize, usize, usize, usize)) -> bool {
+    i >= y && i < y + height && j >= x && j < x + width
+}
+
+#[test]
+fn should_address_nested_views() {
+    let mut image = Image::<Luma>::new(16, 8);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = index as u8;
+    }
+
+    let view = image.view(2, 1, 10, 6).view(3, 2, 4, 3);
+
+    assert_eq!(
+        (view.x(), view.y(), view.width(), view.height()),
+        (5, 3, 4, 3)
+    );
+    assert_eq!(view.offset(), 3 * 16 + 5);
+    assert_eq!(view.pixel(1, 2).y, (4 * 16 + 7) as u8);
+    assert_eq!(
+        view.row(2).iter().map(|pixel| pixel.y).collect::<Vec<_>>(),
+        vec![85, 86, 87, 88]
+    );
+
+    let copy = view.to_image();
+
+    assert_eq!((copy.width, copy.height), (4, 3));
+
+    for i in 0..3 {
+        assert!(copy.pixels[i * 4..(i + 1) * 4] == *view.row(i));
+    }
+
+    let context = view.context();
+
+    assert_eq!(
+        (context.width(), context.height(), context.offset()),
+        (16, 8, 0)
+    );
+}
+
+#[test]
+fn should_write_only_inside_view() {
+    let mut buffer = vec![Luma { y: 7 }; 6 * 5];
+
+    {
+        let mut view = ImageViewMut::new(&mut buffer, 6, 5, 6);
+        let mut region = view.view_mut(1, 1, 3, 2);
+
+        for i in 0..2 {
+            for pixel in region.row_mut(i) {
+                pixel.y = 1;
+            }
+        }
+    }
+
+    for (index, pixel) in buffer.iter().enumerate() {
+        let expected = if inside(index / 6, index % 6, (1, 1, 3, 2)) {
+            1
+        } else {
+            7
+        };
+        assert_eq!(pixel.y, expected);
+    }
+}
+
+#[test]
+fn should_filter_region_with_context_512() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = bilateral_parallel(&image, 5, 3.5, 3.0);
+
+    // Regions touching the border of the image and lying inside of it.
+    for &region in &[(100, 200, 64, 48), (0, 0, 37, 512), (480, 500, 32, 12)] {
+        let (x, y, width, height) = region;
+
+        for parallel in &[false, true] {
+            let mut output = image.as_view().to_image();
+
+            {
+                let source = image.view(x, y, width, height);
+                let mut destination = output.view_mut(x, y, width, height);
+
+                if *parallel {
+                    bilateral_parallel_view(&source, &mut destination, 5, 3.5, 3.0);
+                } else {
+                    bilateral_sequential_view(&source, &mut destination, 5, 3.5, 3.0);
+                }
+            }
+
+            for i in 0..image.height {
+                for j in 0..image.width {
+                    let index = i * image.width + j;
+                    let expected = if inside(i, j, region) {
+                        &reference_output.pixels[index]
+                    } else {
+                        &image.pixels[index]
+                    };
+
+                    assert!(output.pixels[index] == *expected);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn should_filter_strided_buffer() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let crop = image.view(128, 128, 96, 80).to_image();
+    let reference_output = bilateral_sequential(&crop, 5, 3.5, 3.0);
+
+    // Rows padded with pixels the filter must never look at.
+    let stride = 100;
+    let mut source = vec![
+        Pixel {
+            r: 255,
+            g: 0,
+            b: 255
+        };
+        stride * 80
+    ];
+    let mut destination = vec![Pixel::default(); stride * 80];
+
+    for i in 0..80 {
+        source[i * stride..i * stride + 96].clone_from_slice(&crop.pixels[i * 96..(i + 1) * 96]);
+    }
+
+    bilateral_parallel_view(
+        &ImageView::new(&source, 96, 80, stride),
+        &mut ImageViewMut::new(&mut destination, 96, 80, stride),
+        5,
+        3.5,
+        3.0,
+    );
+
+    for i in 0..80 {
+        assert!(
+            destination[i * stride..i * stride + 96]
+                == reference_output.pixels[i * 96..(i + 1) * 96]
+        );
+        assert!(destination[i * stride + 96..(i + 1) * stride]
+            .iter()
+            .all(|pixel| *pixel == Pixel::default()));
+    }
+}
+
+#[test]
+#[should_panic]
+fn should_reject_views_of_different_size() {
+    let source = Image::<Luma>::new(8, 8);
+    let mut destination = Image::<Luma>::new(8, 8);
+
+    bilateral_parallel_luma_view(
+        &source.view(0, 0, 4, 4),
+        &mut destination.view_mut(0, 0, 4, 3),
+        1,
+        3.5,
+        3.0,
+    );
+}
@@ -0,0 +1,158 @@
+extern crate chapter_0;
+
+mod utils;
+
+use std::f64;
+
+use chapter_0::color::ColorSpace;
+use chapter_0::filter::{
+    bilateral_parallel_in_space, bilateral_parallel_planar, bilateral_parallel_with,
+    bilateral_sequential, bilateral_sequential_in_space, bilateral_sequential_planar,
+    bilateral_sequential_with, BorderMode, Options, WindowMode,
+};
+use chapter_0::generate;
+use chapter_0::image::{Image, Luma};
+use chapter_0::metrics::max_abs_error;
+use chapter_0::planar::PlanarImage;
+use chapter_0::transform::{flip_horizontal, rotate180};
+
+use utils::compare_images;
+
+fn options(window: WindowMode) -> Options {
+    Options {
+        window,
+        border: BorderMode::Mirror,
+        ..Options::default()
+    }
+}
+
+// With infinite sigmas every weight is exactly 1, so the filter is a plain box average.
+fn box_filter(image: &Image<Luma<f32>>, window: WindowMode) -> Image<Luma<f32>> {
+    let options = Options {
+        window,
+        ..Options::default()
+    };
+
+    bilateral_sequential_with(image, 1, f64::INFINITY, f64::INFINITY, options)
+}
+
+#[test]
+fn should_keep_legacy_window_by_default() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    let options = Options {
+        window: WindowMode::Legacy,
+        ..Options::default()
+    };
+
+    compare_images(
+        &bilateral_sequential_with(&input, 5, 3.5, 3.0, options),
+        &reference_output,
+    );
+    compare_images(
+        &bilateral_sequential(&input, 5, 3.5, 3.0),
+        &reference_output,
+    );
+}
+
+#[test]
+fn should_cover_whole_window() {
+    let mut impulse = Image::<Luma<f32>>::new(11, 11);
+    impulse.pixels[5 * 11 + 5].y = 36.0;
+
+    let symmetric = box_filter(&impulse, WindowMode::Symmetric);
+    let legacy = box_filter(&impulse, WindowMode::Legacy);
+
+    for i in 0..11 {
+        for j in 0..11 {
+            let inside = |low: usize, high: usize| i >= low && i <= high && j >= low && j <= high;
+
+            let expected = if inside(4, 6) { 4.0 } else { 0.0 };
+            assert_eq!(symmetric.pixels[i * 11 + j].y, expected, "at {}, {}", i, j);
+
+            // Only 2 by 2 pixels, below and to the right of the impulse, see it.
+            let expected = if inside(5, 6) { 9.0 } else { 0.0 };
+            assert_eq!(legacy.pixels[i * 11 + j].y, expected, "at {}, {}", i, j);
+        }
+    }
+}
+
+// Filtering a flipped image and flipping the result back only gives the same image when the
+// window is as wide on both sides of the center.
+fn reoriented<F: Fn(&Image) -> Image>(image: &Image, window: WindowMode, transform: F) -> Image {
+    transform(&bilateral_sequential_with(
+        &transform(image),
+        3,
+        2.0,
+        30.0,
+        options(window),
+    ))
+}
+
+#[test]
+fn should_not_depend_on_orientation() {
+    let image: Image = generate::photographic(64, 48, 5);
+
+    let filtered = bilateral_sequential_with(&image, 3, 2.0, 30.0, options(WindowMode::Symmetric));
+
+    compare_images(
+        &reoriented(&image, WindowMode::Symmetric, flip_horizontal),
+        &filtered,
+    );
+    compare_images(
+        &reoriented(&image, WindowMode::Symmetric, rotate180),
+        &filtered,
+    );
+
+    let filtered = bilateral_sequential_with(&image, 3, 2.0, 30.0, options(WindowMode::Legacy));
+
+    assert!(
+        max_abs_error(
+            &reoriented(&image, WindowMode::Legacy, flip_horizontal),
+            &filtered
+        ) > 5.0
+    );
+    assert!(
+        max_abs_error(
+            &reoriented(&image, WindowMode::Legacy, rotate180),
+            &filtered
+        ) > 5.0
+    );
+}
+
+#[test]
+fn should_agree_between_backends() {
+    let image: Image = generate::photographic(53, 31, 6);
+
+    for &border in &[BorderMode::Shrink, BorderMode::Wrap] {
+        let options = Options {
+            border,
+            window: WindowMode::Symmetric,
+            ..Options::default()
+        };
+
+        compare_images(
+            &bilateral_sequential_with(&image, 4, 3.5, 30.0, options),
+            &bilateral_parallel_with(&image, 4, 3.5, 30.0, options),
+        );
+    }
+}
+
+#[test]
+fn should_cover_whole_window_in_every_layout() {
+    let image: Image = generate::photographic(53, 31, 7);
+    let planar = PlanarImage::from_image(&image);
+    let options = options(WindowMode::Symmetric);
+
+    let reference_output = bilateral_sequential_with(&image, 4, 3.5, 30.0, options);
+
+    for output in &[
+        bilateral_sequential_planar(&planar, 4, 3.5, 30.0, options).to_image(),
+        bilateral_parallel_planar(&planar, 4, 3.5, 30.0, options).to_image(),
+        bilateral_sequential_in_space(&image, 4, 3.5, 30.0, ColorSpace::Rgb, options),
+        bilateral_parallel_in_space(&image, 4, 3.5, 30.0, ColorSpace::Rgb, options),
+    ] {
+        compare_images(output, &reference_output);
+    }
+}
@@ -5,18 +5,271 @@
 #[cfg(not(target_os = "cuda"))]
 compile_error!("This is a CUDA crate. Building it with host toolchain is not possible.");
 
-use core::cmp::{max, min};
+use core::cmp::min;
+use core::ops::{Add, Div, Mul, Neg, Range, Sub};
 
 extern crate nvptx_builtins;
 use nvptx_builtins::*;
 
 extern crate math;
-use math::{exp, sqrt};
+use math::{exp, expf, sqrt, sqrtf};
 
-pub struct Pixel {
-    r: u8,
-    g: u8,
-    b: u8,
+// Same layout as the host pixels, which are copied in as bytes.
+#[repr(C)]
+#[derive(Default)]
+pub struct Pixel<T = u8> {
+    r: T,
+    g: T,
+    b: T,
+}
+
+#[repr(C)]
+#[derive(Default)]
+pub struct Rgba<T = u8> {
+    r: T,
+    g: T,
+    b: T,
+    a: T,
+}
+
+#[repr(C)]
+#[derive(Default)]
+pub struct Luma<T = u8> {
+    y: T,
+}
+
+pub trait Sample: Copy + Default {
+    const INTEGER: bool;
+
+    fn to_f64(self) -> f64;
+    fn from_f64(value: f64) -> Self;
+}
+
+/// Pixel formats made of up to 4 channels of the same `Sample` type.
+///
+/// The first `COLOR_CHANNELS` channels are color, anything after them is alpha.
+pub trait GenericPixel: Default {
+    type Sample: Sample;
+
+    const CHANNELS: usize;
+    const COLOR_CHANNELS: usize;
+
+    fn channel(&self, index: usize) -> Self::Sample;
+    fn set_channel(&mut self, index: usize, value: Self::Sample);
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy)]
+pub enum AlphaMode {
+    Preserve,
+    Filter,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum BorderMode {
+    Shrink,
+    Replicate,
+    Mirror,
+    Wrap,
+    Constant,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy)]
+pub enum WindowMode {
+    Legacy,
+    Symmetric,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy)]
+pub enum Quantization {
+    Truncate,
+    Round,
+    Dither,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy)]
+pub enum Precision {
+    Double,
+    Single,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Options {
+    alpha: AlphaMode,
+    border: BorderMode,
+    border_value: f64,
+    window: WindowMode,
+    quantization: Quantization,
+    dither_seed: u32,
+    precision: Precision,
+}
+
+impl BorderMode {
+    // Same mapping as the host, so that every backend picks the same neighbours.
+    fn locate(self, index: i32, size: i32) -> Option<i32> {
+        if index >= 0 && index < size {
+            return Some(index);
+        }
+
+        match self {
+            BorderMode::Shrink | BorderMode::Constant => None,
+            BorderMode::Replicate => Some(index.min(size - 1).max(0)),
+            BorderMode::Mirror if size == 1 => Some(0),
+            BorderMode::Mirror => {
+                let period = 2 * (size - 1);
+                let index = (index % period + period) % period;
+
+                Some(if index < size { index } else { period - index })
+            }
+            BorderMode::Wrap => Some((index % size + size) % size),
+        }
+    }
+}
+
+impl WindowMode {
+    fn range(self, center: i32, radius: i32) -> Range<i32> {
+        match self {
+            WindowMode::Legacy => center - radius..center + radius,
+            WindowMode::Symmetric => center - radius..center + radius + 1,
+        }
+    }
+}
+
+impl Options {
+    // Same thresholds as the host, so that every backend dithers alike.
+    fn quantize<T: Sample>(&self, value: f64, i: i32, j: i32) -> T {
+        if !T::INTEGER {
+            return T::from_f64(value);
+        }
+
+        let offset = match self.quantization {
+            Quantization::Truncate => 0.0,
+            Quantization::Round => 0.5,
+            Quantization::Dither => {
+                let row = (i + (self.dither_seed / 8 % 8) as i32) & 7;
+                let column = (j + (self.dither_seed % 8) as i32) & 7;
+
+                (BAYER[row as usize][column as usize] as f64 + 0.5) / 64.0
+            }
+        };
+
+        T::from_f64(value + offset)
+    }
+}
+
+const BAYER: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+impl Sample for u8 {
+    const INTEGER: bool = true;
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as u8
+    }
+}
+
+impl Sample for u16 {
+    const INTEGER: bool = true;
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as u16
+    }
+}
+
+impl Sample for f32 {
+    const INTEGER: bool = false;
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+impl<T: Sample> GenericPixel for Pixel<T> {
+    type Sample = T;
+
+    const CHANNELS: usize = 3;
+    const COLOR_CHANNELS: usize = 3;
+
+    fn channel(&self, index: usize) -> T {
+        match index {
+            0 => self.r,
+            1 => self.g,
+            _ => self.b,
+        }
+    }
+
+    fn set_channel(&mut self, index: usize, value: T) {
+        match index {
+            0 => self.r = value,
+            1 => self.g = value,
+            _ => self.b = value,
+        }
+    }
+}
+
+impl<T: Sample> GenericPixel for Rgba<T> {
+    type Sample = T;
+
+    const CHANNELS: usize = 4;
+    const COLOR_CHANNELS: usize = 3;
+
+    fn channel(&self, index: usize) -> T {
+        match index {
+            0 => self.r,
+            1 => self.g,
+            2 => self.b,
+            _ => self.a,
+        }
+    }
+
+    fn set_channel(&mut self, index: usize, value: T) {
+        match index {
+            0 => self.r = value,
+            1 => self.g = value,
+            2 => self.b = value,
+            _ => self.a = value,
+        }
+    }
+}
+
+impl<T: Sample> GenericPixel for Luma<T> {
+    type Sample = T;
+
+    const CHANNELS: usize = 1;
+    const COLOR_CHANNELS: usize = 1;
+
+    fn channel(&self, _index: usize) -> T {
+        self.y
+    }
+
+    fn set_channel(&mut self, _index: usize, value: T) {
+        self.y = value;
+    }
 }
 
 #[no_mangle]
@@ -26,6 +279,167 @@ pub unsafe extern "ptx-kernel" fn bilateral_filter(
     radius: u32,
     sigma_d: f64,
     sigma_r: f64,
+    options: Options,
+) {
+    bilateral(src, dst, radius, sigma_d, sigma_r, options);
+}
+
+#[no_mangle]
+pub unsafe extern "ptx-kernel" fn bilateral_filter_16(
+    src: *const Pixel<u16>,
+    dst: *mut Pixel<u16>,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    bilateral(src, dst, radius, sigma_d, sigma_r, options);
+}
+
+#[no_mangle]
+pub unsafe extern "ptx-kernel" fn bilateral_filter_f32(
+    src: *const Pixel<f32>,
+    dst: *mut Pixel<f32>,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    bilateral(src, dst, radius, sigma_d, sigma_r, options);
+}
+
+#[no_mangle]
+pub unsafe extern "ptx-kernel" fn bilateral_filter_rgba(
+    src: *const Rgba,
+    dst: *mut Rgba,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    bilateral(src, dst, radius, sigma_d, sigma_r, options);
+}
+
+#[no_mangle]
+pub unsafe extern "ptx-kernel" fn bilateral_filter_rgba_16(
+    src: *const Rgba<u16>,
+    dst: *mut Rgba<u16>,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    bilateral(src, dst, radius, sigma_d, sigma_r, options);
+}
+
+#[no_mangle]
+pub unsafe extern "ptx-kernel" fn bilateral_filter_rgba_f32(
+    src: *const Rgba<f32>,
+    dst: *mut Rgba<f32>,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    bilateral(src, dst, radius, sigma_d, sigma_r, options);
+}
+
+#[no_mangle]
+pub unsafe extern "ptx-kernel" fn bilateral_filter_luma(
+    src: *const Luma,
+    dst: *mut Luma,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    bilateral(src, dst, radius, sigma_d, sigma_r, options);
+}
+
+#[no_mangle]
+pub unsafe extern "ptx-kernel" fn bilateral_filter_luma_16(
+    src: *const Luma<u16>,
+    dst: *mut Luma<u16>,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    bilateral(src, dst, radius, sigma_d, sigma_r, options);
+}
+
+#[no_mangle]
+pub unsafe extern "ptx-kernel" fn bilateral_filter_luma_f32(
+    src: *const Luma<f32>,
+    dst: *mut Luma<f32>,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    bilateral(src, dst, radius, sigma_d, sigma_r, options);
+}
+
+#[no_mangle]
+pub unsafe extern "ptx-kernel" fn bilateral_filter_planar(
+    src: *const u8,
+    dst: *mut u8,
+    channels: u32,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    bilateral_planar(src, dst, channels, radius, sigma_d, sigma_r, options);
+}
+
+#[no_mangle]
+pub unsafe extern "ptx-kernel" fn bilateral_filter_planar_16(
+    src: *const u16,
+    dst: *mut u16,
+    channels: u32,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    bilateral_planar(src, dst, channels, radius, sigma_d, sigma_r, options);
+}
+
+#[no_mangle]
+pub unsafe extern "ptx-kernel" fn bilateral_filter_planar_f32(
+    src: *const f32,
+    dst: *mut f32,
+    channels: u32,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    bilateral_planar(src, dst, channels, radius, sigma_d, sigma_r, options);
+}
+
+unsafe fn bilateral<P: GenericPixel>(
+    src: *const P,
+    dst: *mut P,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    match options.precision {
+        Precision::Double => bilateral_in::<P, f64>(src, dst, radius, sigma_d, sigma_r, options),
+        Precision::Single => bilateral_in::<P, f32>(src, dst, radius, sigma_d, sigma_r, options),
+    }
+}
+
+unsafe fn bilateral_in<P: GenericPixel, F: Real>(
+    src: *const P,
+    dst: *mut P,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
 ) {
     let width = grid_dim_x() * block_dim_x();
     let height = grid_dim_y() * block_dim_y();
@@ -38,27 +452,36 @@ pub unsafe extern "ptx-kernel" fn bilateral_filter(
         width: width as i32,
     };
 
-    let mut r_value: f64 = 0.0;
-    let mut r_accum: f64 = 0.0;
+    let center = src_image.pixel(i, j);
+    let (sigma_d, sigma_r) = (F::from_f64(sigma_d), F::from_f64(sigma_r));
+    let mut constant = P::default();
 
-    let mut g_value: f64 = 0.0;
-    let mut g_accum: f64 = 0.0;
+    for channel in 0..P::CHANNELS {
+        constant.set_channel(channel, P::Sample::from_f64(options.border_value));
+    }
 
-    let mut b_value: f64 = 0.0;
-    let mut b_accum: f64 = 0.0;
+    // Formats have at most 4 channels.
+    let mut values = [F::from_f64(0.0); 4];
+    let mut accum = F::from_f64(0.0);
 
-    for k in max(i - radius as i32, 0)..min(i + radius as i32, height as i32) {
-        for l in max(j - radius as i32, 0)..min(j + radius as i32, width as i32) {
-            let w = w_kernel(&src_image, i, j, k, l, sigma_d, sigma_r);
+    for k in options.window.range(i, radius as i32) {
+        for l in options.window.range(j, radius as i32) {
+            let pixel = match (
+                options.border.locate(k, height as i32),
+                options.border.locate(l, width as i32),
+            ) {
+                (Some(y), Some(x)) => src_image.pixel(y, x),
+                _ if options.border == BorderMode::Constant => &constant,
+                _ => continue,
+            };
 
-            r_value = r_value + w * src_image.pixel(k, l).r as f64;
-            r_accum = r_accum + w;
+            let w: F = w_kernel(i, j, k, l, center, pixel, sigma_d, sigma_r);
 
-            g_value = g_value + w * src_image.pixel(k, l).g as f64;
-            g_accum = g_accum + w;
+            for (channel, value) in values.iter_mut().take(P::CHANNELS).enumerate() {
+                *value = *value + w * F::from_f64(pixel.channel(channel).to_f64());
+            }
 
-            b_value = b_value + w * src_image.pixel(k, l).b as f64;
-            b_accum = b_accum + w;
+            accum = accum + w;
         }
     }
 
@@ -67,60 +490,261 @@ pub unsafe extern "ptx-kernel" fn bilateral_filter(
         width: width as i32,
     };
 
-    dst_image.mut_pixel(i, j).r = (r_value / r_accum) as u8;
-    dst_image.mut_pixel(i, j).g = (g_value / g_accum) as u8;
-    dst_image.mut_pixel(i, j).b = (b_value / b_accum) as u8;
+    for (channel, value) in values.iter().take(P::CHANNELS).enumerate() {
+        let sample = match options.alpha {
+            AlphaMode::Preserve if channel >= P::COLOR_CHANNELS => center.channel(channel),
+            _ => options.quantize((*value / accum).to_f64(), i, j),
+        };
+
+        dst_image.mut_pixel(i, j).set_channel(channel, sample);
+    }
+}
+
+unsafe fn bilateral_planar<T: Sample>(
+    src: *const T,
+    dst: *mut T,
+    channels: u32,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    match options.precision {
+        Precision::Double => {
+            bilateral_planar_in::<T, f64>(src, dst, channels, radius, sigma_d, sigma_r, options)
+        }
+        Precision::Single => {
+            bilateral_planar_in::<T, f32>(src, dst, channels, radius, sigma_d, sigma_r, options)
+        }
+    }
+}
+
+unsafe fn bilateral_planar_in<T: Sample, F: Real>(
+    src: *const T,
+    dst: *mut T,
+    channels: u32,
+    radius: u32,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) {
+    let width = grid_dim_x() * block_dim_x();
+    let height = grid_dim_y() * block_dim_y();
+
+    let j = (block_dim_x() * block_idx_x() + thread_idx_x()) as i32;
+    let i = (block_dim_y() * block_idx_y() + thread_idx_y()) as i32;
+
+    let src_planes = Planes {
+        samples: src,
+        width: width as i32,
+        size: (width * height) as isize,
+        channels: min(channels, 4) as usize,
+        constant: T::from_f64(options.border_value).to_f64(),
+    };
+
+    let (sigma_d, sigma_r) = (F::from_f64(sigma_d), F::from_f64(sigma_r));
+
+    // Every thread of a warp reads neighbouring samples of the same plane.
+    let mut values = [F::from_f64(0.0); 4];
+    let mut accum = F::from_f64(0.0);
+
+    for k in options.window.range(i, radius as i32) {
+        for l in options.window.range(j, radius as i32) {
+            let neighbour = match (
+                options.border.locate(k, height as i32),
+                options.border.locate(l, width as i32),
+            ) {
+                (Some(y), Some(x)) => Some((y, x)),
+                _ if options.border == BorderMode::Constant => None,
+                _ => continue,
+            };
+
+            let w: F = w_kernel_planar(&src_planes, i, j, k, l, neighbour, sigma_d, sigma_r);
+
+            for (channel, value) in values.iter_mut().take(src_planes.channels).enumerate() {
+                *value = *value + w * F::from_f64(src_planes.value(channel, neighbour));
+            }
+
+            accum = accum + w;
+        }
+    }
+
+    for (channel, value) in values.iter().take(src_planes.channels).enumerate() {
+        let sample = options.quantize((*value / accum).to_f64(), i, j);
+        *dst.offset(src_planes.index(channel, i, j)) = sample;
+    }
 }
 
-unsafe fn w_kernel(
-    source: &Image,
+unsafe fn w_kernel<P: GenericPixel, F: Real>(
     i: i32,
     j: i32,
     k: i32,
     l: i32,
-    sigma_d: f64,
-    sigma_r: f64,
-) -> f64 {
-    let w_d = ((i - k) * (i - k) + (j - l) * (j - l)) as f64;
-    let w_r = l2_distance(source.pixel(i, j), source.pixel(k, l));
+    lhs: &P,
+    rhs: &P,
+    sigma_d: F,
+    sigma_r: F,
+) -> F {
+    let two = F::from_f64(2.0);
+    let w_d = F::from_f64(((i - k) * (i - k) + (j - l) * (j - l)) as f64);
+    let w_r: F = l2_distance(lhs, rhs);
+
+    (-w_d / (two * sigma_d * sigma_d) - w_r / (two * sigma_r * sigma_r)).exp()
+}
+
+// Only the color channels take part in the range distance: alpha is coverage, not color.
+unsafe fn l2_distance<P: GenericPixel, F: Real>(lhs: &P, rhs: &P) -> F {
+    let mut distance = F::from_f64(0.0);
+
+    for channel in 0..P::COLOR_CHANNELS {
+        let channel_distance =
+            F::from_f64(lhs.channel(channel).to_f64()) - F::from_f64(rhs.channel(channel).to_f64());
+        distance = distance + channel_distance * channel_distance;
+    }
+
+    distance.sqrt()
+}
 
-    exp(-w_d / (2.0 * sigma_d * sigma_d) - w_r / (2.0 * sigma_r * sigma_r))
+// Arithmetic the kernel needs from `f32` and `f64`, so that one body serves both precisions.
+trait Real:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn exp(self) -> Self;
+    fn sqrt(self) -> Self;
 }
 
-unsafe fn l2_distance(lhs: &Pixel, rhs: &Pixel) -> f64 {
-    let r_distance = lhs.r as f64 - rhs.r as f64;
-    let g_distance = lhs.g as f64 - rhs.g as f64;
-    let b_distance = lhs.b as f64 - rhs.b as f64;
+impl Real for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn exp(self) -> Self {
+        exp(self)
+    }
 
-    sqrt(r_distance * r_distance + g_distance * g_distance + b_distance * b_distance)
+    fn sqrt(self) -> Self {
+        sqrt(self)
+    }
 }
 
-struct Image {
-    pixels: *const Pixel,
+impl Real for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn exp(self) -> Self {
+        expf(self)
+    }
+
+    fn sqrt(self) -> Self {
+        sqrtf(self)
+    }
+}
+
+unsafe fn w_kernel_planar<T: Sample, F: Real>(
+    source: &Planes<T>,
+    i: i32,
+    j: i32,
+    k: i32,
+    l: i32,
+    neighbour: Option<(i32, i32)>,
+    sigma_d: F,
+    sigma_r: F,
+) -> F {
+    let two = F::from_f64(2.0);
+    let w_d = F::from_f64(((i - k) * (i - k) + (j - l) * (j - l)) as f64);
+    let w_r: F = l2_distance_planar(source, i, j, neighbour);
+
+    (-w_d / (two * sigma_d * sigma_d) - w_r / (two * sigma_r * sigma_r)).exp()
+}
+
+// A fourth plane is alpha, which stays out of the range distance as it does for `Rgba`.
+unsafe fn l2_distance_planar<T: Sample, F: Real>(
+    source: &Planes<T>,
+    i: i32,
+    j: i32,
+    neighbour: Option<(i32, i32)>,
+) -> F {
+    let channels = source.channels.min(3);
+    let mut distance = F::from_f64(0.0);
+
+    for channel in 0..channels {
+        let channel_distance = F::from_f64(source.value(channel, Some((i, j))))
+            - F::from_f64(source.value(channel, neighbour));
+        distance = distance + channel_distance * channel_distance;
+    }
+
+    distance.sqrt()
+}
+
+struct Image<P = Pixel> {
+    pixels: *const P,
     width: i32,
 }
 
-struct MutImage {
-    pixels: *mut Pixel,
+struct MutImage<P = Pixel> {
+    pixels: *mut P,
     width: i32,
 }
 
-impl Image {
+impl<P> Image<P> {
     fn offset(&self, i: i32, j: i32) -> isize {
         (i * self.width + j) as isize
     }
 
-    unsafe fn pixel(&self, i: i32, j: i32) -> &Pixel {
+    unsafe fn pixel(&self, i: i32, j: i32) -> &P {
         &*self.pixels.offset(self.offset(i, j))
     }
 }
 
-impl MutImage {
+impl<P> MutImage<P> {
     fn offset(&self, i: i32, j: i32) -> isize {
         (i * self.width + j) as isize
     }
 
-    unsafe fn mut_pixel(&mut self, i: i32, j: i32) -> &mut Pixel {
+    unsafe fn mut_pixel(&mut self, i: i32, j: i32) -> &mut P {
         &mut *self.pixels.offset(self.offset(i, j))
     }
 }
+
+struct Planes<T> {
+    samples: *const T,
+    width: i32,
+    size: isize,
+    channels: usize,
+    // Value of every sample past the edges with `BorderMode::Constant`.
+    constant: f64,
+}
+
+impl<T: Sample> Planes<T> {
+    fn index(&self, channel: usize, i: i32, j: i32) -> isize {
+        channel as isize * self.size + (i * self.width + j) as isize
+    }
+
+    unsafe fn sample(&self, channel: usize, i: i32, j: i32) -> &T {
+        &*self.samples.offset(self.index(channel, i, j))
+    }
+
+    // Sample at `position`, `None` standing for the pixels past the edges.
+    unsafe fn value(&self, channel: usize, position: Option<(i32, i32)>) -> f64 {
+        match position {
+            Some((i, j)) => self.sample(channel, i, j).to_f64(),
+            None => self.constant,
+        }
+    }
+}
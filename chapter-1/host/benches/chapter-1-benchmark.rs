@@ -3,15 +3,17 @@ extern crate criterion;
 use criterion::Criterion;
 
 extern crate chapter_1;
+use chapter_1::generate;
 use chapter_1::image::Image;
+use chapter_1::planar::PlanarImage;
 
 fn cuda_bench(criterion: &mut Criterion) {
     use chapter_1::filter::bilateral_cuda as filter;
 
-    let input_512 = Image::open("../../fixtures/input-512.png").unwrap();
-    let input_1024 = Image::open("../../fixtures/input-1024.png").unwrap();
-    let input_2048 = Image::open("../../fixtures/input-2048.png").unwrap();
-    let input_4096 = Image::open("../../fixtures/input-4096.png").unwrap();
+    let input_512: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let input_1024: Image = Image::open("../../fixtures/input-1024.png").unwrap();
+    let input_2048: Image = generate::photographic(2048, 2048, 42);
+    let input_4096: Image = generate::photographic(4096, 4096, 42);
 
     criterion
         .sample_size(20)
@@ -42,5 +44,31 @@ fn cuda_bench(criterion: &mut Criterion) {
         });
 }
 
-criterion_group!(benches, cuda_bench);
+// Neighbouring threads read neighbouring samples of a plane, unlike with interleaved pixels.
+fn cuda_planar_bench(criterion: &mut Criterion) {
+    use chapter_1::filter::{bilateral_cuda_planar as filter, Options};
+
+    let input_512: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let input_1024: Image = Image::open("../../fixtures/input-1024.png").unwrap();
+
+    let planar_512 = PlanarImage::from_image(&input_512);
+    let planar_1024 = PlanarImage::from_image(&input_1024);
+    let options = Options::default();
+
+    criterion
+        .sample_size(20)
+        .without_plots()
+        .bench_function("cuda-planar-512", |b| {
+            b.iter(|| filter(&planar_512, 5, 3.5, 3.0, options).unwrap())
+        });
+
+    criterion
+        .sample_size(20)
+        .without_plots()
+        .bench_function("cuda-planar-1024", |b| {
+            b.iter(|| filter(&planar_1024, 5, 3.5, 3.0, options).unwrap())
+        });
+}
+
+criterion_group!(benches, cuda_bench, cuda_planar_bench);
 criterion_main!(benches);
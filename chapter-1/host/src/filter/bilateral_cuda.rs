@@ -1,8 +1,9 @@
-use std::mem::size_of;
 use cuda::driver;
 use cuda::driver::{Any, Block, Direction, Error as CudaError, Grid};
 
-use image::{Image, Pixel};
+use filter::{AlphaMode, CudaPixel, CudaSample, Options};
+use image::{Image, Luma, Pixel, Rgba};
+use planar::PlanarImage;
 use static_cuda::{CUDA_CTX, CUDA_MODULE};
 
 pub fn filter(
@@ -11,19 +12,125 @@ pub fn filter(
     sigma_d: f64,
     sigma_r: f64,
 ) -> Result<Image, CudaError> {
+    filter_pixel(source, radius, sigma_d, sigma_r)
+}
+
+/// Filters `Pixel` images of any sample type the device has a kernel for.
+pub fn filter_pixel<T: CudaSample>(
+    source: &Image<Pixel<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+) -> Result<Image<Pixel<T>>, CudaError> {
+    filter_with(source, radius, sigma_d, sigma_r, Options::default())
+}
+
+pub fn filter_rgba<T: CudaSample>(
+    source: &Image<Rgba<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    alpha: AlphaMode,
+) -> Result<Image<Rgba<T>>, CudaError> {
+    let options = Options {
+        alpha,
+        ..Options::default()
+    };
+
+    filter_with(source, radius, sigma_d, sigma_r, options)
+}
+
+pub fn filter_luma<T: CudaSample>(
+    source: &Image<Luma<T>>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+) -> Result<Image<Luma<T>>, CudaError> {
+    filter_with(source, radius, sigma_d, sigma_r, Options::default())
+}
+
+/// Filters any pixel format the device has a kernel for, with every behaviour set through
+/// `options`.
+pub fn filter_with<P: CudaPixel>(
+    source: &Image<P>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) -> Result<Image<P>, CudaError> {
     let mut destination = Image::new(source.width, source.height);
-    let kernel = CUDA_MODULE.kernel("bilateral_filter")?;
+    destination.metadata = source.metadata.clone();
+    let kernel = CUDA_MODULE.kernel(&P::kernel_name())?;
+
+    CUDA_CTX.set_current()?;
+
+    let d_src = unsafe { driver::allocate(source.as_bytes().len())? };
+    let d_dst = unsafe { driver::allocate(destination.as_bytes().len())? };
+
+    unsafe {
+        driver::copy(
+            source.as_bytes().as_ptr(),
+            d_src,
+            source.as_bytes().len(),
+            Direction::HostToDevice,
+        )?;
+    }
+
+    kernel.launch(
+        &[
+            Any(&d_src),
+            Any(&d_dst),
+            Any(&(radius as u32)),
+            Any(&sigma_d),
+            Any(&sigma_r),
+            Any(&options),
+        ],
+        Grid::xy(source.width as u32 / 8, source.height as u32 / 8),
+        Block::xy(8, 8),
+    )?;
+
+    unsafe {
+        driver::copy(
+            d_dst,
+            destination.as_bytes_mut().as_mut_ptr(),
+            destination.as_bytes().len(),
+            Direction::DeviceToHost,
+        )?;
+
+        driver::deallocate(d_src)?;
+        driver::deallocate(d_dst)?;
+    }
+
+    Ok(destination)
+}
+
+/// Filters a planar image of up to 4 channels, comparing pixels over all of them but the
+/// fourth, which is alpha like in `Rgba` images.
+///
+/// `options.alpha` doesn't apply: alpha planes are always filtered.
+pub fn filter_planar<T: CudaSample>(
+    source: &PlanarImage<T>,
+    radius: usize,
+    sigma_d: f64,
+    sigma_r: f64,
+    options: Options,
+) -> Result<PlanarImage<T>, CudaError> {
+    assert!(source.channels <= 4, "too many channels for the kernel");
+
+    let mut destination = PlanarImage::new(source.width, source.height, source.channels);
+    destination.metadata = source.metadata.clone();
+    let kernel = CUDA_MODULE.kernel(&format!("bilateral_filter_planar{}", T::KERNEL_SUFFIX))?;
 
     CUDA_CTX.set_current()?;
 
-    let d_src = unsafe { driver::allocate(source.pixels.len() * size_of::<Pixel>())? };
-    let d_dst = unsafe { driver::allocate(destination.pixels.len() * size_of::<Pixel>())? };
+    let d_src = unsafe { driver::allocate(T::as_bytes(&source.samples).len())? };
+    let d_dst = unsafe { driver::allocate(T::as_bytes(&destination.samples).len())? };
 
     unsafe {
         driver::copy(
-            source.pixels.as_ptr(),
-            d_src as *mut Pixel,
-            source.pixels.len(),
+            T::as_bytes(&source.samples).as_ptr(),
+            d_src,
+            T::as_bytes(&source.samples).len(),
             Direction::HostToDevice,
         )?;
     }
@@ -32,9 +139,11 @@ pub fn filter(
         &[
             Any(&d_src),
             Any(&d_dst),
+            Any(&(source.channels as u32)),
             Any(&(radius as u32)),
             Any(&sigma_d),
             Any(&sigma_r),
+            Any(&options),
         ],
         Grid::xy(source.width as u32 / 8, source.height as u32 / 8),
         Block::xy(8, 8),
@@ -42,9 +151,9 @@ pub fn filter(
 
     unsafe {
         driver::copy(
-            d_dst as *mut Pixel,
-            destination.pixels.as_mut_ptr(),
-            destination.pixels.len(),
+            d_dst,
+            T::as_bytes_mut(&mut destination.samples).as_mut_ptr(),
+            T::as_bytes(&destination.samples).len(),
             Direction::DeviceToHost,
         )?;
 
@@ -1,2 +1,216 @@
 mod bilateral_cuda;
 pub use self::bilateral_cuda::filter as bilateral_cuda;
+pub use self::bilateral_cuda::filter_pixel as bilateral_cuda_pixel;
+pub use self::bilateral_cuda::filter_rgba as bilateral_cuda_rgba;
+pub use self::bilateral_cuda::filter_luma as bilateral_cuda_luma;
+pub use self::bilateral_cuda::filter_planar as bilateral_cuda_planar;
+pub use self::bilateral_cuda::filter_with as bilateral_cuda_with;
+
+use std::ops::Range;
+
+use image::{GenericPixel, Luma, Pixel, Plain, Rgba, Sample};
+
+/// Treatment of the alpha channel by the `Rgba` filters.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlphaMode {
+    /// Alpha of every pixel is carried through untouched.
+    Preserve,
+    /// Alpha is smoothed with the same weights as the color channels.
+    Filter,
+}
+
+/// Treatment of the neighbours a window would take from outside of the image.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BorderMode {
+    /// The window is cut at the edges, so edge pixels average over fewer neighbours.
+    Shrink,
+    /// Pixels past the edges repeat the nearest edge pixel.
+    Replicate,
+    /// The image is reflected about its edge pixels, which aren't repeated.
+    Mirror,
+    /// The image repeats: the left edge goes on at the right one, the top at the bottom.
+    Wrap,
+    /// Pixels past the edges have every channel at `Options::border_value`.
+    Constant,
+}
+
+impl BorderMode {
+    /// Position of the pixel standing in for `index` along an axis of `size` pixels.
+    ///
+    /// `None` for positions outside of the image that `Shrink` skips and `Constant` fills.
+    pub fn locate(self, index: i32, size: i32) -> Option<i32> {
+        if index >= 0 && index < size {
+            return Some(index);
+        }
+
+        match self {
+            BorderMode::Shrink | BorderMode::Constant => None,
+            BorderMode::Replicate => Some(index.min(size - 1).max(0)),
+            BorderMode::Mirror if size == 1 => Some(0),
+            BorderMode::Mirror => {
+                let period = 2 * (size - 1);
+                let index = (index % period + period) % period;
+
+                Some(if index < size { index } else { period - index })
+            }
+            BorderMode::Wrap => Some((index % size + size) % size),
+        }
+    }
+}
+
+/// Extent of the window around every pixel.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowMode {
+    /// `-radius..radius`, one pixel short below and to the right of the center.
+    Legacy,
+    /// `-radius..=radius`, centered on the pixel.
+    Symmetric,
+}
+
+impl WindowMode {
+    /// Positions the window covers along an axis, around `center`.
+    pub fn range(self, center: i32, radius: i32) -> Range<i32> {
+        match self {
+            WindowMode::Legacy => center - radius..center + radius,
+            WindowMode::Symmetric => center - radius..center + radius + 1,
+        }
+    }
+}
+
+/// Rounding of filtered values to whole-number samples. Floating point samples are stored
+/// as they are, whatever the mode.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Quantization {
+    /// Values are rounded down, which darkens images by half a step on average.
+    Truncate,
+    /// Values are rounded to the nearest sample.
+    Round,
+    /// Values are rounded up or down following an 8x8 Bayer threshold matrix, so that areas
+    /// keep their exact mean brightness. `Options::dither_seed` shifts the matrix.
+    Dither,
+}
+
+/// Floating point type the kernels weigh and sum pixels in.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Precision {
+    /// `f64` throughout.
+    Double,
+    /// `f32` throughout, exponents and roots included, which consumer GPUs run many times
+    /// faster. 8-bit results are at most one step from `Double`, in well under 0.1% of pixels.
+    Single,
+}
+
+/// Behaviour of the filters besides their radius and sigmas.
+///
+/// `Options::default()` is what the plain filters do, and what the reference images were
+/// made with. Passed to the kernel by value, so the layout of this struct and of the enums
+/// it holds must match the device crate.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Options {
+    /// Only matters for formats with channels besides color.
+    pub alpha: AlphaMode,
+    pub border: BorderMode,
+    /// Sample value of the pixels past the edges with `BorderMode::Constant`.
+    pub border_value: f64,
+    pub window: WindowMode,
+    pub quantization: Quantization,
+    /// Selects one of the 64 placements of the threshold matrix with `Quantization::Dither`.
+    pub dither_seed: u32,
+    pub precision: Precision,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            alpha: AlphaMode::Filter,
+            border: BorderMode::Shrink,
+            border_value: 0.0,
+            window: WindowMode::Legacy,
+            quantization: Quantization::Truncate,
+            dither_seed: 0,
+            precision: Precision::Double,
+        }
+    }
+}
+
+impl Options {
+    /// Converts a filtered `value` of the pixel at row `i`, column `j` to a sample.
+    pub fn quantize<T: Sample>(&self, value: f64, i: i32, j: i32) -> T {
+        if !T::INTEGER {
+            return T::from_f64(value);
+        }
+
+        let offset = match self.quantization {
+            Quantization::Truncate => 0.0,
+            Quantization::Round => 0.5,
+            Quantization::Dither => {
+                let row = (i + (self.dither_seed / 8 % 8) as i32) & 7;
+                let column = (j + (self.dither_seed % 8) as i32) & 7;
+
+                (BAYER[row as usize][column as usize] as f64 + 0.5) / 64.0
+            }
+        };
+
+        T::from_f64(value + offset)
+    }
+}
+
+// Thresholds of ordered dithering, every one of 0..64 spread as evenly as possible.
+const BAYER: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// Sample types the device crate has kernels for.
+pub trait CudaSample: Sample + Plain {
+    /// Appended to the kernel name, e.g. `bilateral_filter_16`.
+    const KERNEL_SUFFIX: &'static str;
+}
+
+impl CudaSample for u8 {
+    const KERNEL_SUFFIX: &'static str = "";
+}
+
+impl CudaSample for u16 {
+    const KERNEL_SUFFIX: &'static str = "_16";
+}
+
+impl CudaSample for f32 {
+    const KERNEL_SUFFIX: &'static str = "_f32";
+}
+
+/// Pixel formats the device crate has kernels for.
+pub trait CudaPixel: GenericPixel + Plain {
+    /// Name of the kernel filtering the format, e.g. `bilateral_filter_rgba_16`.
+    fn kernel_name() -> String;
+}
+
+impl<T: CudaSample> CudaPixel for Pixel<T> {
+    fn kernel_name() -> String {
+        format!("bilateral_filter{}", T::KERNEL_SUFFIX)
+    }
+}
+
+impl<T: CudaSample> CudaPixel for Rgba<T> {
+    fn kernel_name() -> String {
+        format!("bilateral_filter_rgba{}", T::KERNEL_SUFFIX)
+    }
+}
+
+impl<T: CudaSample> CudaPixel for Luma<T> {
+    fn kernel_name() -> String {
+        format!("bilateral_filter_luma{}", T::KERNEL_SUFFIX)
+    }
+}
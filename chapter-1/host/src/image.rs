@@ -1,89 +1,1960 @@
+use std::error::Error;
+use std::fmt;
 use std::fs::File;
-use std::io::BufWriter;
-use std::ptr;
+use std::io;
+use std::io::{BufReader, BufWriter, Chain, Cursor, Read, Write};
+use std::marker::PhantomData;
+use std::mem::{align_of, size_of, size_of_val};
+use std::path::Path;
+use std::slice;
 use png::{BitDepth, ColorType, Decoder, DecodingError, Encoder, EncodingError, HasParameters};
+use png::{Info, Reader, TRANSFORM_IDENTITY};
+use deflate::Compression;
+use deflate::write::ZlibEncoder;
 
-pub struct Image {
-    pub pixels: Vec<Pixel>,
+pub struct Image<P = Pixel> {
+    pub pixels: Vec<P>,
     pub width: usize,
     pub height: usize,
+    pub metadata: Metadata,
 }
 
+/// Ancillary PNG chunks telling how the pixels are meant to be displayed.
+///
+/// Filters copy it from source to destination, and only PNG files store it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    /// `gAMA`: image gamma times 100000.
+    pub gamma: Option<u32>,
+    /// `sRGB`: rendering intent.
+    pub srgb_intent: Option<u8>,
+    /// `iCCP`: embedded color profile.
+    pub icc_profile: Option<IccProfile>,
+    /// `pHYs`: physical pixel dimensions.
+    pub physical_dimensions: Option<PhysicalDimensions>,
+    /// `tEXt`: keyword and text pairs.
+    pub text: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IccProfile {
+    pub name: String,
+    /// Profile compressed with zlib, exactly as stored in the file.
+    pub compressed: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhysicalDimensions {
+    pub x_pixels_per_unit: u32,
+    pub y_pixels_per_unit: u32,
+    /// Whether the unit is the meter, otherwise only the aspect ratio is known.
+    pub meter: bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct Pixel<T = u8> {
+    pub r: T,
+    pub g: T,
+    pub b: T,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct Rgba<T = u8> {
+    pub r: T,
+    pub g: T,
+    pub b: T,
+    pub a: T,
+}
+
+#[repr(C)]
 #[derive(Clone, Debug)]
-pub struct Pixel {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
+pub struct Luma<T = u8> {
+    pub y: T,
+}
+
+/// File formats `Image` can be read from and written to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Png,
+    /// Netpbm graymap, `P5`.
+    Pgm,
+    /// Netpbm pixmap, `P6`.
+    Ppm,
+    /// Netpbm arbitrary map, `P7`.
+    Pam,
+    /// Portable float map of `f32` samples, `PF`.
+    Pfm,
+    /// Radiance RGBE, with a shared exponent for the three channels of a pixel.
+    Hdr,
+}
+
+/// How `Image::open_with` treats files stored differently from the pixel format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Expansion {
+    /// Palette, `tRNS` and sub-byte files are expanded into the pixel format.
+    Allow,
+    /// Only files with exactly the color type and bit depth of the pixel format are accepted.
+    Strict,
+}
+
+/// Reasons for `Image` to fail loading or saving.
+#[derive(Debug)]
+pub enum ImageError {
+    /// The file has a color type the pixel format can't be loaded from.
+    UnsupportedColorType(ColorType),
+    /// The file has a bit depth the pixel format can't be loaded from.
+    UnsupportedBitDepth(BitDepth),
+    /// The file is neither a PNG nor a Netpbm one.
+    UnsupportedFormat,
+    /// The Netpbm, PFM or Radiance header is malformed.
+    InvalidHeader,
+    /// Run length encoded samples overrun their scanline.
+    InvalidData,
+    /// The palette is missing or too short for the indices.
+    InvalidPalette,
+    /// The file ends before all of its samples.
+    TruncatedData,
+    /// The image is too large to be held in memory or stored in the format.
+    DimensionOverflow,
+    /// Interlaced images can't be read row by row.
+    UnsupportedInterlacing,
+    /// The file is not a valid PNG.
+    Decoding(DecodingError),
+    /// The PNG encoder rejected the image.
+    Encoding(EncodingError),
+    /// Reading or writing the underlying stream failed.
+    Io(io::Error),
+}
+
+/// Channel types pixels are made of.
+///
+/// Filters do their math in `f64`. Integer results are quantized as `Options::quantization`
+/// says, `f32` ones are rounded to the nearest value.
+pub trait Sample: Copy + Default + PartialEq {
+    /// Value of a fully saturated channel, which color conversions scale by.
+    const WHITE: f64;
+    /// Whether the sample holds whole numbers only, which filter results are quantized to.
+    const INTEGER: bool;
+
+    fn to_f64(self) -> f64;
+    /// Converts like an `as` cast does, so integers are truncated and saturate.
+    fn from_f64(value: f64) -> Self;
+}
+
+/// Pixel formats made of up to 4 channels of the same `Sample` type.
+///
+/// The first `COLOR_CHANNELS` channels are color, anything after them is alpha.
+pub trait GenericPixel: Clone + Default {
+    type Sample: Sample;
+
+    const CHANNELS: usize;
+    const COLOR_CHANNELS: usize;
+
+    fn channel(&self, index: usize) -> Self::Sample;
+    fn set_channel(&mut self, index: usize, value: Self::Sample);
+}
+
+/// Types which are nothing but samples in memory: no padding, and any bits make a valid value.
+///
+/// Files and devices see pixels through the byte views of this trait.
+///
+/// # Safety
+///
+/// Nothing checks the layout of the implementing type. It must be `#[repr(C)]` or a primitive,
+/// have no padding bytes, and be valid for every bit pattern of its size.
+pub unsafe trait Plain: Sized {
+    fn as_bytes(values: &[Self]) -> &[u8] {
+        let size = size_of_val(values);
+
+        unsafe { slice::from_raw_parts(values.as_ptr() as *const u8, size) }
+    }
+
+    fn as_bytes_mut(values: &mut [Self]) -> &mut [u8] {
+        let size = size_of_val(values);
+
+        unsafe { slice::from_raw_parts_mut(values.as_mut_ptr() as *mut u8, size) }
+    }
+
+    /// Views `bytes` as values, unless they are misaligned or don't make a whole number of them.
+    fn from_bytes(bytes: &[u8]) -> Option<&[Self]> {
+        // Alignments are powers of two, so the low bits of an aligned address are all zero.
+        let aligned = bytes.as_ptr() as usize & (align_of::<Self>() - 1) == 0;
+        let count = bytes.len() / size_of::<Self>();
+
+        if !aligned || count * size_of::<Self>() != bytes.len() {
+            return None;
+        }
+
+        Some(unsafe { slice::from_raw_parts(bytes.as_ptr() as *const Self, count) })
+    }
+}
+
+/// Channel types which can be stored in PNG and Netpbm files.
+pub trait PngSample: Sample + Plain {
+    const BIT_DEPTH: BitDepth;
+
+    /// Converts a buffer of samples between file (big endian) and native byte order.
+    fn swap_bytes(buffer: &mut [u8]);
 }
 
-impl Image {
+/// Pixel formats which `Image` can be read from and written to files with.
+pub trait PngPixel: Clone + Default + Plain {
+    type Sample: PngSample;
+
+    const COLOR_TYPE: ColorType;
+}
+
+impl<P: Clone + Default> Image<P> {
     pub fn new(width: usize, height: usize) -> Self {
         Image {
             width,
             height,
-            pixels: vec![Pixel::default(); width * height],
+            pixels: vec![P::default(); width * height],
+            metadata: Metadata::default(),
         }
     }
+}
+
+impl<P: Plain> Image<P> {
+    /// Pixels in native byte order, as they are sent to devices.
+    pub fn as_bytes(&self) -> &[u8] {
+        P::as_bytes(&self.pixels)
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        P::as_bytes_mut(&mut self.pixels)
+    }
+}
+
+impl<P: PngPixel> Image<P> {
+    pub fn open(path: &str) -> Result<Self, ImageError> {
+        Self::open_with(path, Expansion::Allow)
+    }
+
+    /// Opens a PNG or Netpbm file, recognized by its magic bytes.
+    pub fn open_with(path: &str, expansion: Expansion) -> Result<Self, ImageError> {
+        Self::read_with(BufReader::new(File::open(path)?), expansion)
+    }
+
+    /// Saves the image in the format matching the extension of `path`, PNG if there is none.
+    pub fn save(&self, path: &str) -> Result<(), ImageError> {
+        let format = Format::for_saving(path, Format::Png)?;
+
+        self.write(BufWriter::new(File::create(path)?), format)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ImageError> {
+        Self::read_with(bytes, Expansion::Allow)
+    }
+
+    pub fn to_bytes(&self, format: Format) -> Result<Vec<u8>, ImageError> {
+        let mut bytes = Vec::new();
+
+        self.write(&mut bytes, format)?;
+        Ok(bytes)
+    }
+
+    pub fn read<R: Read>(reader: R) -> Result<Self, ImageError> {
+        Self::read_with(reader, Expansion::Allow)
+    }
+
+    /// Decodes a PNG or Netpbm stream, recognized by its magic bytes.
+    pub fn read_with<R: Read>(mut reader: R, expansion: Expansion) -> Result<Self, ImageError> {
+        let mut magic = [0; 4];
 
-    pub fn open(path: &str) -> Result<Self, DecodingError> {
-        let file = File::open(path)?;
-        let decoder = Decoder::new(file);
-        let (info, mut reader) = decoder.read_info()?;
+        reader.read_exact(&mut magic).map_err(truncated)?;
 
-        if info.color_type != ColorType::RGB {
-            return Err(DecodingError::Other("Color type must be RGB!".into()));
+        // Magic bytes are already consumed, so they are put back in front of the stream.
+        let reader = (&magic[..]).chain(reader);
+
+        match Format::from_magic(&magic) {
+            Some(Format::Png) => Self::decode_png(reader, expansion),
+            Some(Format::Pgm) | Some(Format::Ppm) | Some(Format::Pam) => {
+                Self::decode_netpbm(reader, expansion)
+            }
+            _ => Err(ImageError::UnsupportedFormat),
         }
+    }
 
-        if info.bit_depth != BitDepth::Eight {
-            return Err(DecodingError::Other("Bit depth must be 8!".into()));
+    pub fn write<W: Write>(&self, writer: W, format: Format) -> Result<(), ImageError> {
+        match format {
+            Format::Png => self.encode_png(writer),
+            format => self.encode_netpbm(writer, format),
         }
+    }
+
+    fn decode_png<R: Read>(mut reader: R, expansion: Expansion) -> Result<Self, ImageError> {
+        // The decoder skips the chunks `Metadata` is made of, so they are looked up beforehand.
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let metadata = Metadata::from_png(&bytes);
+        let mut decoder = Decoder::new(&bytes[..]);
+
+        // Expansion is done by `Expander`: the decoder knows nothing about the pixel format,
+        // and its own transformations strip 16-bit samples down to 8 bits.
+        decoder.set(TRANSFORM_IDENTITY);
+
+        let (_, mut reader) = decoder.read_info()?;
+        let expander = Expander::new::<P>(reader.info(), expansion)?;
+
+        let width = reader.info().width as usize;
+        let height = reader.info().height as usize;
+        let row_size = buffer_size(width, 1, pixel_size::<P>())?;
 
-        let mut buffer = vec![0; info.buffer_size()];
-        let mut image = Image::new(info.width as usize, info.height as usize);
+        let mut buffer = vec![0; buffer_size(height, 1, row_size)?];
+        let mut row_index = 0;
 
-        reader.next_frame(&mut buffer)?;
+        while let Some((row, adam7)) = reader.next_interlaced_row()? {
+            let (line, (first_j, first_i, step_j, step_i)) = match adam7 {
+                Some((pass, line, _)) => (line as usize, adam7_layout(pass)),
+                None => (row_index, (0, 0, 1, 1)),
+            };
 
-        unsafe {
-            ptr::copy_nonoverlapping(
-                buffer.as_ptr(),
-                image.pixels.as_mut_ptr() as *mut u8,
-                buffer.len(),
-            );
+            let i = first_i + line * step_i;
+            let destination = &mut buffer[i * row_size..(i + 1) * row_size];
 
-            Ok(image)
+            expander.expand_row::<P>(row, destination, first_j, step_j)?;
+            row_index += 1;
         }
+
+        let mut image = Self::from_big_endian(width, height, buffer);
+
+        image.metadata = metadata;
+        Ok(image)
+    }
+
+    fn decode_netpbm<R: Read>(mut reader: R, expansion: Expansion) -> Result<Self, ImageError> {
+        let header = NetpbmHeader::read(&mut reader)?;
+
+        header.check::<P>(expansion)?;
+
+        let size = buffer_size(header.width, header.height, pixel_size::<P>())?;
+        let mut buffer = vec![0; size];
+
+        reader.read_exact(&mut buffer).map_err(truncated)?;
+        header.rescale::<P>(&mut buffer);
+
+        Ok(Self::from_big_endian(header.width, header.height, buffer))
     }
 
-    pub fn save(&self, path: &str) -> Result<(), EncodingError> {
-        let file = File::create(path)?;
-        let mut encoder = Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+    fn encode_png<W: Write>(&self, writer: W) -> Result<(), ImageError> {
+        if self.width > u32::MAX as usize || self.height > u32::MAX as usize {
+            return Err(ImageError::DimensionOverflow);
+        }
+
+        let mut encoder = Encoder::new(writer, self.width as u32, self.height as u32);
 
-        encoder.set(ColorType::RGB).set(BitDepth::Eight);
+        encoder.set(P::COLOR_TYPE).set(P::Sample::BIT_DEPTH);
 
         let mut writer = encoder.write_header()?;
-        let mut buffer = vec![0; self.pixels.len() * 3];
 
-        unsafe {
-            ptr::copy_nonoverlapping(
-                self.pixels.as_ptr() as *const u8,
-                buffer.as_mut_ptr(),
-                buffer.len(),
-            );
+        for (name, data) in self.metadata.to_png_chunks() {
+            writer.write_chunk(name, &data)?;
         }
 
-        writer.write_image_data(&buffer)?;
+        writer.write_image_data(&self.to_big_endian())?;
+        Ok(())
+    }
+
+    fn encode_netpbm<W: Write>(&self, mut writer: W, format: Format) -> Result<(), ImageError> {
+        write_netpbm_header::<P, _>(&mut writer, format, self.width, self.height)?;
+        writer.write_all(&self.to_big_endian())?;
         Ok(())
     }
+
+    /// Builds an image from samples stored in big endian, like both PNG and Netpbm do.
+    fn from_big_endian(width: usize, height: usize, buffer: Vec<u8>) -> Self {
+        let mut image = Image::new(width, height);
+
+        from_big_endian(&mut image.pixels, buffer);
+        image
+    }
+
+    fn to_big_endian(&self) -> Vec<u8> {
+        to_big_endian(&self.pixels)
+    }
 }
 
-impl Default for Pixel {
-    fn default() -> Self {
-        Pixel { r: 0, g: 0, b: 0 }
+/// Linear floating-point images, stored in PFM and Radiance files.
+///
+/// Neither format has a place for `Metadata`, so it is neither read nor written.
+impl Image<Pixel<f32>> {
+    pub fn open_hdr(path: &str) -> Result<Self, ImageError> {
+        Self::read_hdr(BufReader::new(File::open(path)?))
+    }
+
+    /// Saves the image as PFM or Radiance by the extension of `path`, Radiance if there is none.
+    pub fn save_hdr(&self, path: &str) -> Result<(), ImageError> {
+        let format = Format::for_saving(path, Format::Hdr)?;
+
+        self.write_hdr(BufWriter::new(File::create(path)?), format)
+    }
+
+    /// Decodes a PFM or Radiance stream, recognized by its magic bytes.
+    pub fn read_hdr<R: Read>(mut reader: R) -> Result<Self, ImageError> {
+        let mut magic = [0; 2];
+
+        reader.read_exact(&mut magic).map_err(truncated)?;
+
+        let reader = (&magic[..]).chain(reader);
+
+        match Format::from_magic(&magic) {
+            Some(Format::Pfm) => Self::decode_pfm(reader),
+            Some(Format::Hdr) => Self::decode_radiance(reader),
+            _ => Err(ImageError::UnsupportedFormat),
+        }
+    }
+
+    pub fn write_hdr<W: Write>(&self, writer: W, format: Format) -> Result<(), ImageError> {
+        match format {
+            Format::Pfm => self.encode_pfm(writer),
+            Format::Hdr => self.encode_radiance(writer),
+            _ => Err(ImageError::UnsupportedFormat),
+        }
+    }
+
+    fn decode_pfm<R: Read>(mut reader: R) -> Result<Self, ImageError> {
+        if netpbm_token(&mut reader)? != "PF" {
+            return Err(ImageError::UnsupportedColorType(ColorType::Grayscale));
+        }
+
+        let width = netpbm_number(&mut reader)?;
+        let height = netpbm_number(&mut reader)?;
+
+        // Only the sign of the scale matters, a negative one marks little endian samples.
+        let scale = match netpbm_token(&mut reader)?.parse::<f32>() {
+            Ok(scale) if scale != 0.0 && scale.is_finite() => scale,
+            _ => return Err(ImageError::InvalidHeader),
+        };
+
+        if width == 0 || height == 0 {
+            return Err(ImageError::InvalidHeader);
+        }
+
+        buffer_size(width, height, 12)?;
+
+        let mut row = vec![0; width * 12];
+        let mut image = Image::new(width, height);
+
+        // Rows are stored from the bottom up.
+        for i in (0..height).rev() {
+            reader.read_exact(&mut row).map_err(truncated)?;
+
+            let destination = &mut image.pixels[i * width..(i + 1) * width];
+
+            for (pixel, bytes) in destination.iter_mut().zip(row.chunks(12)) {
+                let sample = |offset: usize| {
+                    let bytes = &bytes[offset..offset + 4];
+                    let bits = if scale < 0.0 {
+                        bytes
+                            .iter()
+                            .rev()
+                            .fold(0, |bits, &byte| bits << 8 | byte as u32)
+                    } else {
+                        be_u32(bytes)
+                    };
+
+                    f32::from_bits(bits)
+                };
+
+                *pixel = Pixel {
+                    r: sample(0),
+                    g: sample(4),
+                    b: sample(8),
+                };
+            }
+        }
+
+        Ok(image)
+    }
+
+    fn decode_radiance<R: Read>(mut reader: R) -> Result<Self, ImageError> {
+        if !radiance_line(&mut reader)?.starts_with("#?") {
+            return Err(ImageError::InvalidHeader);
+        }
+
+        // Variables up to an empty line, of which only the format matters.
+        loop {
+            let line = radiance_line(&mut reader)?;
+
+            if line.is_empty() {
+                break;
+            }
+
+            if line.starts_with("FORMAT=") && line != "FORMAT=32-bit_rle_rgbe" {
+                return Err(ImageError::UnsupportedFormat);
+            }
+        }
+
+        // Only the usual orientation is supported: rows from the top, pixels from the left.
+        let resolution = radiance_line(&mut reader)?;
+        let tokens = resolution.split_whitespace().collect::<Vec<_>>();
+
+        if tokens.len() != 4 || tokens[0] != "-Y" || tokens[2] != "+X" {
+            return Err(ImageError::InvalidHeader);
+        }
+
+        let (height, width) = match (tokens[1].parse(), tokens[3].parse()) {
+            (Ok(height), Ok(width)) if height > 0 && width > 0 => (height, width),
+            _ => return Err(ImageError::InvalidHeader),
+        };
+
+        buffer_size(width, height, 4)?;
+
+        let mut scanline = vec![0; width * 4];
+        let mut image = Image::new(width, height);
+
+        for row in image.pixels.chunks_mut(width) {
+            read_radiance_scanline(&mut reader, &mut scanline)?;
+
+            for (pixel, rgbe) in row.iter_mut().zip(scanline.chunks(4)) {
+                *pixel = from_rgbe(rgbe);
+            }
+        }
+
+        Ok(image)
+    }
+
+    fn encode_pfm<W: Write>(&self, mut writer: W) -> Result<(), ImageError> {
+        write!(writer, "PF\n{} {}\n-1.0\n", self.width, self.height)?;
+
+        let mut row = Vec::with_capacity(self.width * 12);
+
+        for i in (0..self.height).rev() {
+            row.clear();
+
+            for pixel in &self.pixels[i * self.width..(i + 1) * self.width] {
+                for sample in &[pixel.r, pixel.g, pixel.b] {
+                    row.extend(to_be_bytes(sample.to_bits()).iter().rev());
+                }
+            }
+
+            writer.write_all(&row)?;
+        }
+
+        Ok(())
+    }
+
+    fn encode_radiance<W: Write>(&self, mut writer: W) -> Result<(), ImageError> {
+        write!(
+            writer,
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            self.height, self.width
+        )?;
+
+        // Run length encoding is only defined for these widths.
+        let encoded = self.width >= 8 && self.width < 0x8000;
+        let mut channel = Vec::with_capacity(self.width);
+
+        for i in 0..self.height {
+            let row = &self.pixels[i * self.width..(i + 1) * self.width];
+            let scanline = row.iter().map(to_rgbe).collect::<Vec<_>>();
+
+            if !encoded {
+                for rgbe in &scanline {
+                    writer.write_all(rgbe)?;
+                }
+
+                continue;
+            }
+
+            writer.write_all(&[2, 2, (self.width >> 8) as u8, self.width as u8])?;
+
+            for index in 0..4 {
+                channel.clear();
+                channel.extend(scanline.iter().map(|rgbe| rgbe[index]));
+
+                write_radiance_runs(&mut writer, &channel)?;
+            }
+        }
+
+        Ok(())
     }
 }
 
-impl PartialEq<Pixel> for Pixel {
-    fn eq(&self, other: &Pixel) -> bool {
-        self.r == other.r && self.g == other.g && self.b == other.b
+/// Reads an image one row at a time, for images too large to be held in memory at once.
+///
+/// Interlaced PNG files spread every row over the whole file, so they can't be read this way.
+pub struct RowReader<P, R: Read> {
+    pub width: usize,
+    pub height: usize,
+    pub metadata: Metadata,
+    source: RowSource<R>,
+    row_size: usize,
+    rows_read: usize,
+    pixel: PhantomData<P>,
+}
+
+/// Bytes already consumed from a stream, put back in front of it.
+type Prefixed<R> = Chain<Cursor<Vec<u8>>, R>;
+
+// Decoders and encoders are boxed, as they are far larger than the Netpbm variants.
+enum RowSource<R: Read> {
+    Png(Box<Reader<Prefixed<R>>>, Expander),
+    Netpbm(Prefixed<R>, NetpbmHeader),
+}
+
+impl<P: PngPixel, R: Read> RowReader<P, R> {
+    pub fn new(reader: R) -> Result<Self, ImageError> {
+        Self::with_expansion(reader, Expansion::Allow)
+    }
+
+    /// Reads the header of a PNG or Netpbm stream, recognized by its magic bytes.
+    pub fn with_expansion(mut reader: R, expansion: Expansion) -> Result<Self, ImageError> {
+        let mut magic = [0; 4];
+
+        reader.read_exact(&mut magic).map_err(truncated)?;
+
+        let (source, width, height, metadata) = match Format::from_magic(&magic) {
+            Some(Format::Png) => {
+                // Chunks up to the image data are kept aside for `Metadata`, then replayed
+                // to the decoder.
+                let (prefix, metadata) = read_png_prefix(&magic, &mut reader)?;
+                let mut decoder = Decoder::new(Cursor::new(prefix).chain(reader));
+
+                decoder.set(TRANSFORM_IDENTITY);
+
+                let (_, reader) = decoder.read_info()?;
+
+                if reader.info().interlaced {
+                    return Err(ImageError::UnsupportedInterlacing);
+                }
+
+                let expander = Expander::new::<P>(reader.info(), expansion)?;
+                let width = reader.info().width as usize;
+                let height = reader.info().height as usize;
+
+                (
+                    RowSource::Png(Box::new(reader), expander),
+                    width,
+                    height,
+                    metadata,
+                )
+            }
+
+            Some(Format::Pgm) | Some(Format::Ppm) | Some(Format::Pam) => {
+                let mut reader = Cursor::new(magic.to_vec()).chain(reader);
+                let header = NetpbmHeader::read(&mut reader)?;
+
+                header.check::<P>(expansion)?;
+
+                let (width, height) = (header.width, header.height);
+
+                (
+                    RowSource::Netpbm(reader, header),
+                    width,
+                    height,
+                    Metadata::default(),
+                )
+            }
+
+            _ => return Err(ImageError::UnsupportedFormat),
+        };
+
+        Ok(RowReader {
+            width,
+            height,
+            metadata,
+            source,
+            row_size: buffer_size(width, 1, pixel_size::<P>())?,
+            rows_read: 0,
+            pixel: PhantomData,
+        })
+    }
+
+    /// Reads the next row into `row`, which has to be `width` pixels long.
+    pub fn read_row(&mut self, row: &mut [P]) -> Result<(), ImageError> {
+        assert_eq!(row.len(), self.width);
+
+        if self.rows_read == self.height {
+            return Err(ImageError::TruncatedData);
+        }
+
+        let mut buffer = vec![0; self.row_size];
+
+        match self.source {
+            RowSource::Png(ref mut reader, ref expander) => match reader.next_row()? {
+                Some(data) => expander.expand_row::<P>(data, &mut buffer, 0, 1)?,
+                None => return Err(ImageError::TruncatedData),
+            },
+
+            RowSource::Netpbm(ref mut reader, ref header) => {
+                reader.read_exact(&mut buffer).map_err(truncated)?;
+                header.rescale::<P>(&mut buffer);
+            }
+        }
+
+        from_big_endian(row, buffer);
+        self.rows_read += 1;
+        Ok(())
+    }
+}
+
+/// Writes an image one row at a time, from top to bottom.
+///
+/// PNG image data is compressed as the rows come, so only the compressor's window is held.
+pub struct RowWriter<P, W: Write> {
+    pub width: usize,
+    pub height: usize,
+    sink: RowSink<W>,
+    rows_written: usize,
+    pixel: PhantomData<P>,
+}
+
+enum RowSink<W: Write> {
+    Png(Box<ZlibEncoder<IdatWriter<W>>>),
+    Netpbm(W),
+}
+
+impl<P: PngPixel, W: Write> RowWriter<P, W> {
+    /// Writes the header of a `width` by `height` image, and the metadata PNG files store.
+    pub fn new(
+        mut writer: W,
+        format: Format,
+        width: usize,
+        height: usize,
+        metadata: &Metadata,
+    ) -> Result<Self, ImageError> {
+        let sink = match format {
+            Format::Png => {
+                if width > u32::MAX as usize || height > u32::MAX as usize {
+                    return Err(ImageError::DimensionOverflow);
+                }
+
+                let crc_table = crc_table();
+                let mut header = to_be_bytes(width as u32).to_vec();
+
+                header.extend_from_slice(&to_be_bytes(height as u32));
+                header.extend_from_slice(&[
+                    P::Sample::BIT_DEPTH as u8,
+                    P::COLOR_TYPE as u8,
+                    0,
+                    0,
+                    0,
+                ]);
+
+                writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+                write_png_chunk(&mut writer, &crc_table, b"IHDR", &header)?;
+
+                for (name, data) in metadata.to_png_chunks() {
+                    write_png_chunk(&mut writer, &crc_table, &name, &data)?;
+                }
+
+                let idat = IdatWriter {
+                    writer,
+                    crc_table,
+                    buffer: Vec::new(),
+                };
+
+                RowSink::Png(Box::new(ZlibEncoder::new(idat, Compression::Default)))
+            }
+
+            format => {
+                write_netpbm_header::<P, _>(&mut writer, format, width, height)?;
+                RowSink::Netpbm(writer)
+            }
+        };
+
+        Ok(RowWriter {
+            width,
+            height,
+            sink,
+            rows_written: 0,
+            pixel: PhantomData,
+        })
+    }
+
+    /// Writes the next row, which has to be `width` pixels long.
+    pub fn write_row(&mut self, row: &[P]) -> Result<(), ImageError> {
+        assert_eq!(row.len(), self.width);
+        assert!(
+            self.rows_written < self.height,
+            "all rows are already written"
+        );
+
+        match self.sink {
+            // Rows are stored unfiltered, preceded by the filter type.
+            RowSink::Png(ref mut encoder) => {
+                encoder.write_all(&[0])?;
+                encoder.write_all(&to_big_endian(row))?;
+            }
+
+            RowSink::Netpbm(ref mut writer) => writer.write_all(&to_big_endian(row))?,
+        }
+
+        self.rows_written += 1;
+        Ok(())
+    }
+
+    /// Completes the file once all of the rows are written, and returns the underlying writer.
+    pub fn finish(self) -> Result<W, ImageError> {
+        if self.rows_written < self.height {
+            return Err(ImageError::TruncatedData);
+        }
+
+        match self.sink {
+            RowSink::Png(encoder) => Ok(encoder.finish()?.finish()?),
+            RowSink::Netpbm(mut writer) => {
+                writer.flush()?;
+                Ok(writer)
+            }
+        }
+    }
+}
+
+/// Splits the compressed image data into `IDAT` chunks.
+struct IdatWriter<W: Write> {
+    writer: W,
+    crc_table: [u32; 256],
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> IdatWriter<W> {
+    const CHUNK_SIZE: usize = 1 << 16;
+
+    fn finish(mut self) -> io::Result<W> {
+        if !self.buffer.is_empty() {
+            write_png_chunk(&mut self.writer, &self.crc_table, b"IDAT", &self.buffer)?;
+        }
+
+        write_png_chunk(&mut self.writer, &self.crc_table, b"IEND", &[])?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Write for IdatWriter<W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(bytes);
+
+        if self.buffer.len() >= Self::CHUNK_SIZE {
+            write_png_chunk(&mut self.writer, &self.crc_table, b"IDAT", &self.buffer)?;
+            self.buffer.clear();
+        }
+
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_png_chunk<W: Write>(
+    writer: &mut W,
+    crc_table: &[u32; 256],
+    name: &[u8; 4],
+    data: &[u8],
+) -> io::Result<()> {
+    let crc = crc32(crc_table, crc32(crc_table, !0, name), data);
+
+    writer.write_all(&to_be_bytes(data.len() as u32))?;
+    writer.write_all(name)?;
+    writer.write_all(data)?;
+    writer.write_all(&to_be_bytes(!crc))
+}
+
+fn crc32(crc_table: &[u32; 256], crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, &byte| {
+        crc_table[((crc ^ byte as u32) & 0xff) as usize] ^ crc >> 8
+    })
+}
+
+/// Lookup table of the CRC-32 PNG chunks end with.
+fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+
+    for (index, entry) in table.iter_mut().enumerate() {
+        *entry = (0..8).fold(index as u32, |crc, _| {
+            if crc & 1 == 1 {
+                0xedb8_8320 ^ crc >> 1
+            } else {
+                crc >> 1
+            }
+        });
+    }
+
+    table
+}
+
+/// Reads the PNG signature and the chunks before the image data, given the magic bytes.
+fn read_png_prefix<R: Read>(
+    magic: &[u8],
+    reader: &mut R,
+) -> Result<(Vec<u8>, Metadata), ImageError> {
+    let mut metadata = Metadata::default();
+    let mut prefix = magic.to_vec();
+
+    prefix.resize(8, 0);
+    reader.read_exact(&mut prefix[4..]).map_err(truncated)?;
+
+    loop {
+        let start = prefix.len();
+
+        prefix.resize(start + 8, 0);
+        reader.read_exact(&mut prefix[start..]).map_err(truncated)?;
+
+        let length = be_u32(&prefix[start..]) as u64;
+        let mut name = [0; 4];
+        name.copy_from_slice(&prefix[start + 4..start + 8]);
+
+        if &name == b"IDAT" || &name == b"IEND" {
+            return Ok((prefix, metadata));
+        }
+
+        // Data and CRC, read without trusting the length for an allocation.
+        let expected = length + 4;
+
+        if reader.by_ref().take(expected).read_to_end(&mut prefix)? as u64 != expected {
+            return Err(ImageError::TruncatedData);
+        }
+
+        metadata.add_png_chunk(&name, &prefix[start + 8..prefix.len() - 4]);
+    }
+}
+
+/// Copies samples stored in big endian into pixels.
+fn from_big_endian<P: PngPixel>(pixels: &mut [P], mut buffer: Vec<u8>) {
+    assert_eq!(buffer.len(), pixels.len() * pixel_size::<P>());
+
+    P::Sample::swap_bytes(&mut buffer);
+    P::as_bytes_mut(pixels).copy_from_slice(&buffer);
+}
+
+fn to_big_endian<P: PngPixel>(pixels: &[P]) -> Vec<u8> {
+    let mut buffer = P::as_bytes(pixels).to_vec();
+
+    P::Sample::swap_bytes(&mut buffer);
+    buffer
+}
+
+/// Size in bytes of a pixel in both memory and files.
+fn pixel_size<P: PngPixel>() -> usize {
+    P::COLOR_TYPE.samples() * size_of::<P::Sample>()
+}
+
+fn write_netpbm_header<P: PngPixel, W: Write>(
+    writer: &mut W,
+    format: Format,
+    width: usize,
+    height: usize,
+) -> Result<(), ImageError> {
+    let maxval = (1u32 << P::Sample::BIT_DEPTH as u32) - 1;
+
+    match (format, P::COLOR_TYPE) {
+        (Format::Pgm, ColorType::Grayscale) => {
+            write!(writer, "P5\n{} {}\n{}\n", width, height, maxval)?
+        }
+
+        (Format::Ppm, ColorType::RGB) => write!(writer, "P6\n{} {}\n{}\n", width, height, maxval)?,
+
+        (Format::Pam, color_type) => write!(
+            writer,
+            "P7\nWIDTH {}\nHEIGHT {}\nDEPTH {}\nMAXVAL {}\nTUPLTYPE {}\nENDHDR\n",
+            width,
+            height,
+            color_type.samples(),
+            maxval,
+            tuple_type(color_type)
+        )?,
+
+        (Format::Pfm, _) | (Format::Hdr, _) => return Err(ImageError::UnsupportedFormat),
+
+        (_, color_type) => return Err(ImageError::UnsupportedColorType(color_type)),
+    }
+
+    Ok(())
+}
+
+impl Metadata {
+    /// Collects the known ancillary chunks of a PNG file, ignoring malformed ones.
+    fn from_png(bytes: &[u8]) -> Self {
+        let mut metadata = Metadata::default();
+        let mut offset = 8;
+
+        while offset + 8 <= bytes.len() {
+            let length = be_u32(&bytes[offset..]) as usize;
+            let name = &bytes[offset + 4..offset + 8];
+
+            let data = match bytes.get(offset + 8..offset + 8 + length) {
+                Some(data) => data,
+                None => break,
+            };
+
+            metadata.add_png_chunk(name, data);
+
+            // Length, name and CRC around the data.
+            offset += length + 12;
+        }
+
+        metadata
+    }
+
+    /// Takes over a chunk if it is one of the known ancillary ones.
+    fn add_png_chunk(&mut self, name: &[u8], data: &[u8]) {
+        match (name, data.len()) {
+            (b"gAMA", 4) => self.gamma = Some(be_u32(data)),
+            (b"sRGB", 1) => self.srgb_intent = Some(data[0]),
+
+            (b"pHYs", 9) => {
+                self.physical_dimensions = Some(PhysicalDimensions {
+                    x_pixels_per_unit: be_u32(data),
+                    y_pixels_per_unit: be_u32(&data[4..]),
+                    meter: data[8] == 1,
+                })
+            }
+
+            // Keyword, separator, compression method and the profile itself.
+            (b"iCCP", length) => {
+                if let Some(separator) = data.iter().position(|&byte| byte == 0) {
+                    if separator + 2 <= length {
+                        self.icc_profile = Some(IccProfile {
+                            name: latin1(&data[..separator]),
+                            compressed: data[separator + 2..].to_vec(),
+                        });
+                    }
+                }
+            }
+
+            (b"tEXt", _) => {
+                if let Some(separator) = data.iter().position(|&byte| byte == 0) {
+                    let keyword = latin1(&data[..separator]);
+                    self.text.push((keyword, latin1(&data[separator + 1..])));
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    fn to_png_chunks(&self) -> Vec<([u8; 4], Vec<u8>)> {
+        let mut chunks = Vec::new();
+
+        if let Some(gamma) = self.gamma {
+            chunks.push((*b"gAMA", to_be_bytes(gamma).to_vec()));
+        }
+
+        if let Some(ref profile) = self.icc_profile {
+            let mut data = to_latin1(&profile.name);
+            data.extend_from_slice(&[0, 0]);
+            data.extend_from_slice(&profile.compressed);
+            chunks.push((*b"iCCP", data));
+        }
+
+        if let Some(intent) = self.srgb_intent {
+            chunks.push((*b"sRGB", vec![intent]));
+        }
+
+        if let Some(dimensions) = self.physical_dimensions {
+            let mut data = to_be_bytes(dimensions.x_pixels_per_unit).to_vec();
+            data.extend_from_slice(&to_be_bytes(dimensions.y_pixels_per_unit));
+            data.push(dimensions.meter as u8);
+            chunks.push((*b"pHYs", data));
+        }
+
+        for entry in &self.text {
+            let mut data = to_latin1(&entry.0);
+            data.push(0);
+            data.extend_from_slice(&to_latin1(&entry.1));
+            chunks.push((*b"tEXt", data));
+        }
+
+        chunks
+    }
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    (bytes[0] as u32) << 24 | (bytes[1] as u32) << 16 | (bytes[2] as u32) << 8 | bytes[3] as u32
+}
+
+fn to_be_bytes(value: u32) -> [u8; 4] {
+    [
+        (value >> 24) as u8,
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+    ]
+}
+
+/// PNG text is Latin-1, which maps to the first 256 code points one to one.
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&byte| byte as char).collect()
+}
+
+fn to_latin1(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| if (c as u32) < 256 { c as u8 } else { b'?' })
+        .collect()
+}
+
+impl Format {
+    /// Guesses the format from the extension of `path`.
+    pub fn from_path(path: &str) -> Option<Format> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+
+        match &extension[..] {
+            "png" => Some(Format::Png),
+            "pgm" => Some(Format::Pgm),
+            "ppm" => Some(Format::Ppm),
+            "pam" => Some(Format::Pam),
+            "pfm" => Some(Format::Pfm),
+            "hdr" => Some(Format::Hdr),
+            _ => None,
+        }
+    }
+
+    /// Format to save `path` in: `default` without an extension, an error for unknown ones.
+    fn for_saving(path: &str, default: Format) -> Result<Format, ImageError> {
+        match Path::new(path).extension() {
+            Some(_) => Format::from_path(path).ok_or(ImageError::UnsupportedFormat),
+            None => Ok(default),
+        }
+    }
+
+    /// Recognizes the format from the first bytes of a file.
+    pub fn from_magic(bytes: &[u8]) -> Option<Format> {
+        if bytes.starts_with(b"\x89PNG") {
+            Some(Format::Png)
+        } else if bytes.starts_with(b"P5") {
+            Some(Format::Pgm)
+        } else if bytes.starts_with(b"P6") {
+            Some(Format::Ppm)
+        } else if bytes.starts_with(b"P7") {
+            Some(Format::Pam)
+        } else if bytes.starts_with(b"PF") || bytes.starts_with(b"Pf") {
+            Some(Format::Pfm)
+        } else if bytes.starts_with(b"#?") {
+            Some(Format::Hdr)
+        } else {
+            None
+        }
+    }
+}
+
+struct NetpbmHeader {
+    width: usize,
+    height: usize,
+    depth: usize,
+    maxval: u32,
+}
+
+impl NetpbmHeader {
+    fn read<R: Read>(reader: &mut R) -> Result<Self, ImageError> {
+        let magic = netpbm_token(reader)?;
+
+        let header = if magic == "P7" {
+            Self::read_pam(reader)?
+        } else {
+            NetpbmHeader {
+                width: netpbm_number(reader)?,
+                height: netpbm_number(reader)?,
+                depth: if magic == "P5" { 1 } else { 3 },
+                maxval: netpbm_maxval(reader)?,
+            }
+        };
+
+        let complete = header.width > 0 && header.height > 0 && header.depth > 0;
+
+        // PAM headers may leave the maximum value out.
+        if !complete || header.maxval == 0 {
+            return Err(ImageError::InvalidHeader);
+        }
+
+        Ok(header)
+    }
+
+    fn read_pam<R: Read>(reader: &mut R) -> Result<Self, ImageError> {
+        let mut header = NetpbmHeader {
+            width: 0,
+            height: 0,
+            depth: 0,
+            maxval: 0,
+        };
+
+        // Tuple types only name what the depth already tells.
+        loop {
+            match &netpbm_token(reader)?[..] {
+                "WIDTH" => header.width = netpbm_number(reader)?,
+                "HEIGHT" => header.height = netpbm_number(reader)?,
+                "DEPTH" => header.depth = netpbm_number(reader)?,
+                "MAXVAL" => header.maxval = netpbm_maxval(reader)?,
+                "TUPLTYPE" => {
+                    netpbm_token(reader)?;
+                }
+                "ENDHDR" => break,
+                _ => return Err(ImageError::InvalidHeader),
+            }
+        }
+
+        Ok(header)
+    }
+
+    /// Makes sure the samples can be loaded into the pixel format, maybe after `rescale`.
+    fn check<P: PngPixel>(&self, expansion: Expansion) -> Result<(), ImageError> {
+        let color_type = match self.depth {
+            1 => ColorType::Grayscale,
+            2 => ColorType::GrayscaleAlpha,
+            3 => ColorType::RGB,
+            4 => ColorType::RGBA,
+            _ => return Err(ImageError::InvalidHeader),
+        };
+
+        if color_type != P::COLOR_TYPE {
+            return Err(ImageError::UnsupportedColorType(color_type));
+        }
+
+        // Samples take two bytes once the maximum value doesn't fit into one.
+        let bit_depth = if self.maxval < 256 {
+            BitDepth::Eight
+        } else {
+            BitDepth::Sixteen
+        };
+
+        let scaled = self.maxval != target_maxval::<P>();
+
+        if bit_depth != P::Sample::BIT_DEPTH || (scaled && expansion == Expansion::Strict) {
+            return Err(ImageError::UnsupportedBitDepth(bit_depth));
+        }
+
+        Ok(())
+    }
+
+    /// Scales big endian samples up to the full range of the pixel format, rounding to nearest.
+    fn rescale<P: PngPixel>(&self, buffer: &mut [u8]) {
+        let target_maxval = target_maxval::<P>();
+
+        if self.maxval == target_maxval {
+            return;
+        }
+
+        for sample in buffer.chunks_mut(size_of::<P::Sample>()) {
+            let value = sample
+                .iter()
+                .fold(0, |value, &byte| value << 8 | byte as u32);
+            let value = (value.min(self.maxval) * target_maxval + self.maxval / 2) / self.maxval;
+
+            for (index, byte) in sample.iter_mut().rev().enumerate() {
+                *byte = (value >> (index * 8)) as u8;
+            }
+        }
+    }
+}
+
+fn target_maxval<P: PngPixel>() -> u32 {
+    (1 << P::Sample::BIT_DEPTH as u32) - 1
+}
+
+/// Reads the next whitespace separated token of a Netpbm header, skipping comments.
+///
+/// Exactly one whitespace character after the token is consumed, as it separates the last
+/// token from the raster.
+fn netpbm_token<R: Read>(reader: &mut R) -> Result<String, ImageError> {
+    let mut token = String::new();
+    let mut comment = false;
+
+    loop {
+        let mut byte = [0];
+
+        if reader.read(&mut byte)? == 0 {
+            return Err(ImageError::TruncatedData);
+        }
+
+        match byte[0] {
+            b'\n' | b'\r' if comment => comment = false,
+            _ if comment => {}
+            b'#' if token.is_empty() => comment = true,
+            b' ' | b'\t' | b'\n' | b'\r' if token.is_empty() => {}
+            b' ' | b'\t' | b'\n' | b'\r' => return Ok(token),
+            byte => token.push(byte as char),
+        }
+    }
+}
+
+fn netpbm_number<R: Read>(reader: &mut R) -> Result<usize, ImageError> {
+    match netpbm_token(reader)?.parse() {
+        Ok(number) => Ok(number),
+        Err(_) => Err(ImageError::InvalidHeader),
+    }
+}
+
+/// Reads a maximum sample value, which has to fit into 16 bits.
+fn netpbm_maxval<R: Read>(reader: &mut R) -> Result<u32, ImageError> {
+    let maxval = netpbm_number(reader)?;
+
+    if maxval == 0 || maxval > 65535 {
+        return Err(ImageError::InvalidHeader);
+    }
+
+    Ok(maxval as u32)
+}
+
+/// Reads a line of a Radiance header, without the line feed.
+fn radiance_line<R: Read>(reader: &mut R) -> Result<String, ImageError> {
+    let mut line = String::new();
+
+    loop {
+        let mut byte = [0];
+
+        if reader.read(&mut byte)? == 0 {
+            return Err(ImageError::TruncatedData);
+        }
+
+        match byte[0] {
+            b'\n' => return Ok(line),
+            byte => line.push(byte as char),
+        }
+    }
+}
+
+/// Reads a scanline of RGBE pixels, either flat or run length encoded channel by channel.
+///
+/// Run length encoding of the original format, which repeats the previous pixel, isn't
+/// supported: no writer of the last decades produces it.
+fn read_radiance_scanline<R: Read>(reader: &mut R, scanline: &mut [u8]) -> Result<(), ImageError> {
+    let width = scanline.len() / 4;
+    let mut start = [0; 4];
+
+    reader.read_exact(&mut start).map_err(truncated)?;
+
+    // Encoded scanlines start with an otherwise invalid pixel, holding the width.
+    let encoded = (8..0x8000).contains(&width) && start[0] == 2 && start[1] == 2 && start[2] < 128;
+
+    if !encoded {
+        scanline[..4].copy_from_slice(&start);
+        reader.read_exact(&mut scanline[4..]).map_err(truncated)?;
+
+        return Ok(());
+    }
+
+    if (start[2] as usize) << 8 | start[3] as usize != width {
+        return Err(ImageError::InvalidData);
+    }
+
+    for channel in 0..4 {
+        let mut j = 0;
+
+        while j < width {
+            let mut bytes = [0; 128];
+
+            reader.read_exact(&mut bytes[..1]).map_err(truncated)?;
+
+            // Counts above 128 are runs of a single value, others spans of literal values.
+            let (run, length) = match bytes[0] {
+                count if count > 128 => (true, count as usize - 128),
+                count => (false, count as usize),
+            };
+
+            if length == 0 || j + length > width {
+                return Err(ImageError::InvalidData);
+            }
+
+            if run {
+                reader.read_exact(&mut bytes[..1]).map_err(truncated)?;
+
+                for k in j..j + length {
+                    scanline[k * 4 + channel] = bytes[0];
+                }
+            } else {
+                reader.read_exact(&mut bytes[..length]).map_err(truncated)?;
+
+                for (k, &byte) in (j..j + length).zip(bytes.iter()) {
+                    scanline[k * 4 + channel] = byte;
+                }
+            }
+
+            j += length;
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes a channel of a scanline into runs of at least four values and literal spans.
+fn write_radiance_runs<W: Write>(writer: &mut W, data: &[u8]) -> io::Result<()> {
+    let mut start = 0;
+
+    while start < data.len() {
+        let mut run_start = start;
+        let mut run_length = 0;
+
+        while run_start < data.len() {
+            let value = data[run_start];
+
+            run_length = data[run_start..]
+                .iter()
+                .take(127)
+                .take_while(|&&byte| byte == value)
+                .count();
+
+            if run_length >= 4 {
+                break;
+            }
+
+            run_start += run_length;
+        }
+
+        for literal in data[start..run_start].chunks(128) {
+            writer.write_all(&[literal.len() as u8])?;
+            writer.write_all(literal)?;
+        }
+
+        if run_start < data.len() {
+            writer.write_all(&[128 + run_length as u8, data[run_start]])?;
+        }
+
+        start = run_start + run_length;
+    }
+
+    Ok(())
+}
+
+/// Converts a pixel to the shared exponent representation of Radiance files.
+///
+/// Negative and tiny channels become zero, huge ones saturate.
+fn to_rgbe(pixel: &Pixel<f32>) -> [u8; 4] {
+    let value = pixel.r.max(pixel.g).max(pixel.b).min(1.7e38);
+
+    if value.is_nan() || value <= 1e-32 {
+        return [0; 4];
+    }
+
+    // Splits `value` into a mantissa in [0.5, 1) and a power of two, like C's `frexp`.
+    let bits = value.to_bits();
+    let exponent = ((bits >> 23) & 0xff) as i32 - 126;
+    let mantissa = f32::from_bits(bits & 0x807f_ffff | 126 << 23);
+
+    let scale = mantissa as f64 * 256.0 / value as f64;
+    let channel = |sample: f32| (sample.max(0.0).min(value) as f64 * scale) as u8;
+
+    [
+        channel(pixel.r),
+        channel(pixel.g),
+        channel(pixel.b),
+        (exponent + 128) as u8,
+    ]
+}
+
+fn from_rgbe(rgbe: &[u8]) -> Pixel<f32> {
+    if rgbe[3] == 0 {
+        return Pixel::default();
+    }
+
+    let scale = 2f64.powi(rgbe[3] as i32 - (128 + 8));
+
+    Pixel {
+        r: (rgbe[0] as f64 * scale) as f32,
+        g: (rgbe[1] as f64 * scale) as f32,
+        b: (rgbe[2] as f64 * scale) as f32,
+    }
+}
+
+fn truncated(error: io::Error) -> ImageError {
+    match error.kind() {
+        io::ErrorKind::UnexpectedEof => ImageError::TruncatedData,
+        _ => ImageError::Io(error),
+    }
+}
+
+fn tuple_type(color_type: ColorType) -> &'static str {
+    match color_type {
+        ColorType::Grayscale => "GRAYSCALE",
+        ColorType::GrayscaleAlpha => "GRAYSCALE_ALPHA",
+        ColorType::RGB => "RGB",
+        _ => "RGB_ALPHA",
+    }
+}
+
+/// Converts samples of a PNG file into the samples of a pixel format.
+struct Expander {
+    conversion: Conversion,
+    bit_depth: u8,
+    samples: usize,
+    palette: Vec<u8>,
+    trns: Vec<u16>,
+}
+
+enum Conversion {
+    /// Samples are stored exactly like the pixel format wants them.
+    Copy,
+    /// Indices are looked up in the palette, with alpha taken from `tRNS`.
+    Palette,
+    /// Sub-byte gray levels are scaled up to 8 bits.
+    Scale,
+    /// Color matching the `tRNS` key becomes transparent, any other opaque.
+    ColorKey,
+}
+
+impl Expander {
+    fn new<P: PngPixel>(info: &Info, expansion: Expansion) -> Result<Self, ImageError> {
+        let target_depth = P::Sample::BIT_DEPTH;
+        let exact = info.color_type == P::COLOR_TYPE && info.bit_depth == target_depth;
+        let sub_byte = (info.bit_depth as u8) < 8;
+
+        let conversion = match (info.color_type, P::COLOR_TYPE, target_depth) {
+            _ if exact => Conversion::Copy,
+            _ if expansion == Expansion::Strict => return Err(mismatch::<P>(info)),
+
+            (ColorType::Indexed, ColorType::RGB, BitDepth::Eight)
+            | (ColorType::Indexed, ColorType::RGBA, BitDepth::Eight) => Conversion::Palette,
+
+            (ColorType::Grayscale, ColorType::Grayscale, BitDepth::Eight) if sub_byte => {
+                Conversion::Scale
+            }
+
+            (ColorType::RGB, ColorType::RGBA, _) if info.trns.is_some() => {
+                if info.bit_depth != target_depth {
+                    return Err(ImageError::UnsupportedBitDepth(info.bit_depth));
+                }
+
+                Conversion::ColorKey
+            }
+
+            _ => return Err(mismatch::<P>(info)),
+        };
+
+        let palette = match conversion {
+            Conversion::Palette => match info.palette {
+                Some(ref palette) => palette.clone(),
+                None => return Err(ImageError::InvalidPalette),
+            },
+            _ => Vec::new(),
+        };
+
+        // The decoder keeps only the low byte of 8-bit color keys, 16-bit ones stay big endian.
+        let trns = match info.trns {
+            Some(ref trns) => match conversion {
+                Conversion::ColorKey if info.bit_depth == BitDepth::Sixteen => trns
+                    .chunks(2)
+                    .map(|key| (key[0] as u16) << 8 | key[1] as u16)
+                    .collect(),
+                _ => trns.iter().map(|&sample| sample as u16).collect(),
+            },
+            None => Vec::new(),
+        };
+
+        Ok(Expander {
+            conversion,
+            bit_depth: info.bit_depth as u8,
+            samples: info.color_type.samples(),
+            palette,
+            trns,
+        })
+    }
+
+    /// Splits a row into samples of the file's bit depth.
+    fn unpack(&self, row: &[u8]) -> Vec<u16> {
+        match self.bit_depth {
+            8 => row.iter().map(|&byte| byte as u16).collect(),
+            16 => row
+                .chunks(2)
+                .map(|pair| (pair[0] as u16) << 8 | pair[1] as u16)
+                .collect(),
+
+            depth => {
+                let mask = (1 << depth) - 1;
+
+                row.iter()
+                    .flat_map(|&byte| {
+                        (1..8 / depth + 1).map(move |n| ((byte >> (8 - n * depth)) & mask) as u16)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Expands a row of the file into big endian samples of the pixel format.
+    ///
+    /// Pixels of the row are stored `step` pixels apart from the `first` one, as Adam7 passes
+    /// need it.
+    fn expand_row<P: PngPixel>(
+        &self,
+        row: &[u8],
+        destination: &mut [u8],
+        first: usize,
+        step: usize,
+    ) -> Result<(), ImageError> {
+        let sample_size = size_of::<P::Sample>();
+        let width = destination.len() / pixel_size::<P>();
+        let mut expanded = vec![0; P::COLOR_TYPE.samples()];
+
+        for (n, pixel) in self.unpack(row).chunks(self.samples).enumerate() {
+            let j = first + n * step;
+
+            // Sub-byte rows are padded up to a whole byte.
+            if j >= width {
+                break;
+            }
+
+            self.expand(pixel, &mut expanded)?;
+
+            let offset = j * pixel_size::<P>();
+
+            for (index, sample) in expanded.iter().enumerate() {
+                if sample_size == 1 {
+                    destination[offset + index] = *sample as u8;
+                } else {
+                    destination[offset + index * 2] = (*sample >> 8) as u8;
+                    destination[offset + index * 2 + 1] = *sample as u8;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn expand(&self, source: &[u16], destination: &mut [u16]) -> Result<(), ImageError> {
+        match self.conversion {
+            Conversion::Copy => destination.copy_from_slice(source),
+
+            Conversion::Palette => {
+                let index = source[0] as usize;
+                let color = match self.palette.get(index * 3..index * 3 + 3) {
+                    Some(color) => color,
+                    None => return Err(ImageError::InvalidPalette),
+                };
+
+                destination[0] = color[0] as u16;
+                destination[1] = color[1] as u16;
+                destination[2] = color[2] as u16;
+
+                if destination.len() == 4 {
+                    destination[3] = *self.trns.get(index).unwrap_or(&255);
+                }
+            }
+
+            Conversion::Scale => {
+                destination[0] = source[0] * 255 / ((1 << self.bit_depth) - 1);
+            }
+
+            Conversion::ColorKey => {
+                destination[..3].copy_from_slice(source);
+                destination[3] = if source == &self.trns[..] {
+                    0
+                } else {
+                    ((1u32 << self.bit_depth) - 1) as u16
+                };
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn mismatch<P: PngPixel>(info: &Info) -> ImageError {
+    if info.color_type != P::COLOR_TYPE {
+        ImageError::UnsupportedColorType(info.color_type)
+    } else {
+        ImageError::UnsupportedBitDepth(info.bit_depth)
+    }
+}
+
+/// Size in bytes of a `width` by `height` image, unless it doesn't fit into memory at all.
+fn buffer_size(width: usize, height: usize, pixel_size: usize) -> Result<usize, ImageError> {
+    width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(pixel_size))
+        .ok_or(ImageError::DimensionOverflow)
+}
+
+/// First column, first row and their steps of the pixels stored in an Adam7 pass.
+fn adam7_layout(pass: u8) -> (usize, usize, usize, usize) {
+    match pass {
+        1 => (0, 0, 8, 8),
+        2 => (4, 0, 8, 8),
+        3 => (0, 4, 4, 8),
+        4 => (2, 0, 4, 4),
+        5 => (0, 2, 2, 4),
+        6 => (1, 0, 2, 2),
+        _ => (0, 1, 1, 2),
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(error: io::Error) -> Self {
+        ImageError::Io(error)
+    }
+}
+
+impl From<DecodingError> for ImageError {
+    fn from(error: DecodingError) -> Self {
+        match error {
+            DecodingError::IoError(error) => truncated(error),
+            // The decoder has no dedicated error for streams ending too early.
+            DecodingError::Format(ref message) if message == "unexpected EOF" => {
+                ImageError::TruncatedData
+            }
+            error => ImageError::Decoding(error),
+        }
+    }
+}
+
+impl From<EncodingError> for ImageError {
+    fn from(error: EncodingError) -> Self {
+        match error {
+            EncodingError::IoError(error) => ImageError::Io(error),
+            error => ImageError::Encoding(error),
+        }
+    }
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ImageError::UnsupportedColorType(color_type) => {
+                write!(f, "Unsupported color type: {:?}", color_type)
+            }
+            ImageError::UnsupportedBitDepth(bit_depth) => {
+                write!(f, "Unsupported bit depth: {}", bit_depth as u8)
+            }
+            ImageError::UnsupportedFormat => write!(f, "Unsupported file format"),
+            ImageError::InvalidHeader => write!(f, "Invalid image header"),
+            ImageError::InvalidData => write!(f, "Invalid run length encoded data"),
+            ImageError::InvalidPalette => write!(f, "Invalid palette"),
+            ImageError::TruncatedData => write!(f, "Image data is truncated"),
+            ImageError::DimensionOverflow => write!(f, "Image dimensions are too large"),
+            ImageError::UnsupportedInterlacing => write!(f, "Interlaced images can't be streamed"),
+            ImageError::Decoding(ref error) => write!(f, "PNG decoding failed: {}", error),
+            ImageError::Encoding(ref error) => write!(f, "PNG encoding failed: {}", error),
+            ImageError::Io(ref error) => write!(f, "I/O error: {}", error),
+        }
+    }
+}
+
+impl Error for ImageError {
+    fn description(&self) -> &str {
+        match *self {
+            ImageError::UnsupportedColorType(_) => "unsupported color type",
+            ImageError::UnsupportedBitDepth(_) => "unsupported bit depth",
+            ImageError::UnsupportedFormat => "unsupported file format",
+            ImageError::InvalidHeader => "invalid image header",
+            ImageError::InvalidData => "invalid run length encoded data",
+            ImageError::InvalidPalette => "invalid palette",
+            ImageError::TruncatedData => "truncated image data",
+            ImageError::DimensionOverflow => "image dimensions are too large",
+            ImageError::UnsupportedInterlacing => "interlaced images can't be streamed",
+            ImageError::Decoding(_) => "PNG decoding failed",
+            ImageError::Encoding(_) => "PNG encoding failed",
+            ImageError::Io(_) => "I/O error",
+        }
+    }
+}
+
+impl Sample for u8 {
+    const WHITE: f64 = 255.0;
+    const INTEGER: bool = true;
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as u8
+    }
+}
+
+impl Sample for u16 {
+    const WHITE: f64 = 65535.0;
+    const INTEGER: bool = true;
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as u16
+    }
+}
+
+impl Sample for f32 {
+    const WHITE: f64 = 1.0;
+    const INTEGER: bool = false;
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+impl<T: Sample> GenericPixel for Pixel<T> {
+    type Sample = T;
+
+    const CHANNELS: usize = 3;
+    const COLOR_CHANNELS: usize = 3;
+
+    fn channel(&self, index: usize) -> T {
+        match index {
+            0 => self.r,
+            1 => self.g,
+            _ => self.b,
+        }
+    }
+
+    fn set_channel(&mut self, index: usize, value: T) {
+        match index {
+            0 => self.r = value,
+            1 => self.g = value,
+            _ => self.b = value,
+        }
+    }
+}
+
+impl<T: Sample> GenericPixel for Rgba<T> {
+    type Sample = T;
+
+    const CHANNELS: usize = 4;
+    const COLOR_CHANNELS: usize = 3;
+
+    fn channel(&self, index: usize) -> T {
+        match index {
+            0 => self.r,
+            1 => self.g,
+            2 => self.b,
+            _ => self.a,
+        }
+    }
+
+    fn set_channel(&mut self, index: usize, value: T) {
+        match index {
+            0 => self.r = value,
+            1 => self.g = value,
+            2 => self.b = value,
+            _ => self.a = value,
+        }
+    }
+}
+
+impl<T: Sample> GenericPixel for Luma<T> {
+    type Sample = T;
+
+    const CHANNELS: usize = 1;
+    const COLOR_CHANNELS: usize = 1;
+
+    fn channel(&self, _index: usize) -> T {
+        self.y
+    }
+
+    fn set_channel(&mut self, _index: usize, value: T) {
+        self.y = value;
+    }
+}
+
+unsafe impl Plain for u8 {}
+
+unsafe impl Plain for u16 {}
+
+unsafe impl Plain for f32 {}
+
+// `#[repr(C)]` structs of a single sample type have no padding.
+unsafe impl<T: Plain> Plain for Pixel<T> {}
+
+unsafe impl<T: Plain> Plain for Rgba<T> {}
+
+unsafe impl<T: Plain> Plain for Luma<T> {}
+
+// Files and devices take pixels for runs of samples. Each of these stops compiling if a pixel
+// gets padded, or aligned stricter than its samples.
+macro_rules! assert_layout {
+    ($name:ident, $pixel:ty, $sample:ty, $channels:expr) => {
+        const $name: (
+            [(); $channels * size_of::<$sample>()],
+            [(); align_of::<$sample>()],
+        ) = ([(); size_of::<$pixel>()], [(); align_of::<$pixel>()]);
+    };
+}
+
+assert_layout!(_PIXEL_8_LAYOUT, Pixel<u8>, u8, 3);
+assert_layout!(_PIXEL_16_LAYOUT, Pixel<u16>, u16, 3);
+assert_layout!(_PIXEL_F32_LAYOUT, Pixel<f32>, f32, 3);
+assert_layout!(_RGBA_8_LAYOUT, Rgba<u8>, u8, 4);
+assert_layout!(_RGBA_16_LAYOUT, Rgba<u16>, u16, 4);
+assert_layout!(_RGBA_F32_LAYOUT, Rgba<f32>, f32, 4);
+assert_layout!(_LUMA_8_LAYOUT, Luma<u8>, u8, 1);
+assert_layout!(_LUMA_16_LAYOUT, Luma<u16>, u16, 1);
+assert_layout!(_LUMA_F32_LAYOUT, Luma<f32>, f32, 1);
+
+impl PngSample for u8 {
+    const BIT_DEPTH: BitDepth = BitDepth::Eight;
+
+    fn swap_bytes(_buffer: &mut [u8]) {}
+}
+
+impl PngSample for u16 {
+    const BIT_DEPTH: BitDepth = BitDepth::Sixteen;
+
+    fn swap_bytes(buffer: &mut [u8]) {
+        if cfg!(target_endian = "little") {
+            for sample in buffer.chunks_mut(2) {
+                sample.swap(0, 1);
+            }
+        }
+    }
+}
+
+impl<T: PngSample> PngPixel for Pixel<T> {
+    type Sample = T;
+
+    const COLOR_TYPE: ColorType = ColorType::RGB;
+}
+
+impl<T: PngSample> PngPixel for Rgba<T> {
+    type Sample = T;
+
+    const COLOR_TYPE: ColorType = ColorType::RGBA;
+}
+
+impl<T: PngSample> PngPixel for Luma<T> {
+    type Sample = T;
+
+    const COLOR_TYPE: ColorType = ColorType::Grayscale;
+}
+
+impl<T: Sample> Default for Pixel<T> {
+    fn default() -> Self {
+        Pixel {
+            r: T::default(),
+            g: T::default(),
+            b: T::default(),
+        }
+    }
+}
+
+impl<T: Sample> PartialEq<Pixel<T>> for Pixel<T> {
+    fn eq(&self, other: &Pixel<T>) -> bool {
+        self.r == other.r && self.g == other.g && self.b == other.b
+    }
+}
+
+impl<T: Sample> Default for Rgba<T> {
+    fn default() -> Self {
+        Rgba {
+            r: T::default(),
+            g: T::default(),
+            b: T::default(),
+            a: T::default(),
+        }
+    }
+}
+
+impl<T: Sample> PartialEq<Rgba<T>> for Rgba<T> {
+    fn eq(&self, other: &Rgba<T>) -> bool {
+        self.r == other.r && self.g == other.g && self.b == other.b && self.a == other.a
+    }
+}
+
+impl<T: Sample> Default for Luma<T> {
+    fn default() -> Self {
+        Luma { y: T::default() }
+    }
+}
+
+impl<T: Sample> PartialEq<Luma<T>> for Luma<T> {
+    fn eq(&self, other: &Luma<T>) -> bool {
+        self.y == other.y
     }
 }
@@ -4,9 +4,15 @@
 extern crate lazy_static;
 
 extern crate cuda;
+extern crate deflate;
 extern crate png;
 
 mod static_cuda;
 
 pub mod image;
 pub mod filter;
+pub mod generate;
+pub mod metrics;
+pub mod noise;
+pub mod planar;
+pub mod transform;
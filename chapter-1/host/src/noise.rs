@@ -0,0 +1,146 @@
+use std::f64;
+
+use generate::Random;
+use image::{GenericPixel, Image, Sample};
+use metrics::psnr;
+
+// Above this mean, Poisson counts are drawn from their normal approximation.
+const POISSON_NORMAL_LIMIT: f64 = 64.0;
+
+/// PSNR of a noisy image and of its filtered version, both against the clean image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recovery {
+    pub noisy_psnr: f64,
+    pub filtered_psnr: f64,
+}
+
+impl Recovery {
+    /// Decibels won by filtering, negative when the filter makes things worse.
+    pub fn gain(&self) -> f64 {
+        self.filtered_psnr - self.noisy_psnr
+    }
+}
+
+/// Adds zero mean Gaussian noise of standard deviation `sigma`, in units of the samples.
+///
+/// Only color channels are touched, alpha is kept as it is. Results never go below zero, and
+/// integer ones are clamped to white as well: float samples may be HDR, brighter than white.
+/// Integer results are rounded to the nearest value, so the mean stays where it was.
+pub fn gaussian<P: GenericPixel>(image: &Image<P>, sigma: f64, seed: u64) -> Image<P> {
+    let mut random = Random::new(seed);
+
+    map_colors(image, |value| value + sigma * normal(&mut random))
+}
+
+/// Replaces samples with photon counts drawn around them, `peak` photons being white.
+///
+/// Noise grows with the square root of brightness, so lower peaks are noisier.
+pub fn poisson<P: GenericPixel>(image: &Image<P>, peak: f64, seed: u64) -> Image<P> {
+    let mut random = Random::new(seed);
+    let white = P::Sample::WHITE;
+
+    map_colors(image, |value| {
+        poisson_count(&mut random, value / white * peak) / peak * white
+    })
+}
+
+/// Turns a `density` fraction of pixels black or white, with even odds.
+///
+/// All color channels of a hit pixel change together, like dead and stuck sensor cells.
+pub fn salt_and_pepper<P: GenericPixel>(image: &Image<P>, density: f64, seed: u64) -> Image<P> {
+    let mut random = Random::new(seed);
+    let mut noisy = copy(image);
+
+    for pixel in &mut noisy.pixels {
+        if random.next_f64() >= density {
+            continue;
+        }
+
+        let value = if random.next_f64() < 0.5 {
+            0.0
+        } else {
+            P::Sample::WHITE
+        };
+
+        for channel in 0..P::COLOR_CHANNELS {
+            pixel.set_channel(channel, P::Sample::from_f64(value));
+        }
+    }
+
+    noisy
+}
+
+/// Measures how much of the damage `filter` undoes on `noisy`, a noisy copy of `clean`.
+///
+/// `filter` is usually a bilateral filter with the parameters being tuned, for example
+/// `|image| bilateral_parallel(image, 5, 3.5, 30.0)`.
+pub fn recovery<P: GenericPixel, F: Fn(&Image<P>) -> Image<P>>(
+    clean: &Image<P>,
+    noisy: &Image<P>,
+    filter: F,
+) -> Recovery {
+    Recovery {
+        noisy_psnr: psnr(noisy, clean),
+        filtered_psnr: psnr(&filter(noisy), clean),
+    }
+}
+
+fn map_colors<P: GenericPixel, F: FnMut(f64) -> f64>(
+    image: &Image<P>,
+    mut function: F,
+) -> Image<P> {
+    let mut noisy = copy(image);
+
+    for pixel in &mut noisy.pixels {
+        for channel in 0..P::COLOR_CHANNELS {
+            let mut value = function(pixel.channel(channel).to_f64()).max(0.0);
+
+            if P::Sample::INTEGER {
+                value = value.min(P::Sample::WHITE).round();
+            }
+
+            pixel.set_channel(channel, P::Sample::from_f64(value));
+        }
+    }
+
+    noisy
+}
+
+fn copy<P: GenericPixel>(image: &Image<P>) -> Image<P> {
+    Image {
+        pixels: image.pixels.clone(),
+        width: image.width,
+        height: image.height,
+        metadata: image.metadata.clone(),
+    }
+}
+
+// Box-Muller transform, throwing away the second value to keep the stream simple.
+fn normal(random: &mut Random) -> f64 {
+    let radius = (-2.0 * (1.0 - random.next_f64()).ln()).sqrt();
+    let angle = 2.0 * f64::consts::PI * random.next_f64();
+
+    radius * angle.cos()
+}
+
+// Knuth's multiplication method for small means, which is exact but linear in `mean`.
+fn poisson_count(random: &mut Random, mean: f64) -> f64 {
+    if mean <= 0.0 {
+        return 0.0;
+    }
+
+    if mean > POISSON_NORMAL_LIMIT {
+        return (mean + mean.sqrt() * normal(random)).round().max(0.0);
+    }
+
+    let limit = (-mean).exp();
+    let mut product = random.next_f64();
+    let mut count = 0.0;
+
+    while product > limit {
+        product *= random.next_f64();
+        count += 1.0;
+    }
+
+    count
+}
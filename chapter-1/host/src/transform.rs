@@ -0,0 +1,194 @@
+use std::mem;
+
+use filter::BorderMode;
+use image::{Image, Metadata};
+
+// Metadata rules: everything is carried over, except that transforms which swap the axes
+// swap the physical pixel dimensions with them.
+
+/// How `pad` fills the pixels outside of the image.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Padding<P> {
+    /// Every new pixel is the given one.
+    Constant(P),
+    /// New pixels repeat the nearest edge pixel.
+    Replicate,
+    /// The image is reflected about its edge pixels, which aren't repeated: `cba|abc` would
+    /// repeat `a`, mirroring gives `cb|abc|ba`.
+    ///
+    /// Paddings wider than the image keep bouncing between the edges.
+    Mirror,
+}
+
+/// Copies the `width` by `height` region whose top left pixel is at column `x`, row `y`.
+///
+/// Panics if the region doesn't fit into the image.
+pub fn crop<P: Clone + Default>(
+    image: &Image<P>,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> Image<P> {
+    assert!(
+        x + width <= image.width && y + height <= image.height,
+        "region is out of the image"
+    );
+
+    remap(image, width, height, image.metadata.clone(), |i, j| {
+        (y + i, x + j)
+    })
+}
+
+/// Grows the image by the given number of pixels on every side.
+pub fn pad<P: Clone + Default>(
+    image: &Image<P>,
+    left: usize,
+    top: usize,
+    right: usize,
+    bottom: usize,
+    padding: &Padding<P>,
+) -> Image<P> {
+    let width = left + image.width + right;
+    let height = top + image.height + bottom;
+
+    let mut padded = Image::new(width, height);
+    padded.metadata = image.metadata.clone();
+
+    if image.width == 0 || image.height == 0 {
+        if let Padding::Constant(ref value) = *padding {
+            for pixel in &mut padded.pixels {
+                *pixel = value.clone();
+            }
+        }
+
+        return padded;
+    }
+
+    // Pixels are located as the filters locate their neighbours past the edges.
+    let border = match *padding {
+        Padding::Constant(_) => BorderMode::Constant,
+        Padding::Replicate => BorderMode::Replicate,
+        Padding::Mirror => BorderMode::Mirror,
+    };
+
+    for i in 0..height {
+        for j in 0..width {
+            let source = (
+                border.locate(i as i32 - top as i32, image.height as i32),
+                border.locate(j as i32 - left as i32, image.width as i32),
+            );
+
+            padded.pixels[i * width + j] = match source {
+                (Some(y), Some(x)) => image.pixels[y as usize * image.width + x as usize].clone(),
+                _ => match *padding {
+                    Padding::Constant(ref value) => value.clone(),
+                    _ => unreachable!("only constant padding leaves pixels outside of the image"),
+                },
+            };
+        }
+    }
+
+    padded
+}
+
+/// Mirrors the image left to right.
+pub fn flip_horizontal<P: Clone + Default>(image: &Image<P>) -> Image<P> {
+    remap(
+        image,
+        image.width,
+        image.height,
+        image.metadata.clone(),
+        |i, j| (i, image.width - 1 - j),
+    )
+}
+
+/// Mirrors the image top to bottom.
+pub fn flip_vertical<P: Clone + Default>(image: &Image<P>) -> Image<P> {
+    remap(
+        image,
+        image.width,
+        image.height,
+        image.metadata.clone(),
+        |i, j| (image.height - 1 - i, j),
+    )
+}
+
+/// Rotates the image a quarter turn clockwise.
+pub fn rotate90<P: Clone + Default>(image: &Image<P>) -> Image<P> {
+    remap(
+        image,
+        image.height,
+        image.width,
+        swapped_metadata(image),
+        |i, j| (image.height - 1 - j, i),
+    )
+}
+
+/// Turns the image upside down.
+pub fn rotate180<P: Clone + Default>(image: &Image<P>) -> Image<P> {
+    remap(
+        image,
+        image.width,
+        image.height,
+        image.metadata.clone(),
+        |i, j| (image.height - 1 - i, image.width - 1 - j),
+    )
+}
+
+/// Rotates the image a quarter turn counterclockwise.
+pub fn rotate270<P: Clone + Default>(image: &Image<P>) -> Image<P> {
+    remap(
+        image,
+        image.height,
+        image.width,
+        swapped_metadata(image),
+        |i, j| (j, image.width - 1 - i),
+    )
+}
+
+/// Swaps rows and columns, mirroring the image about its main diagonal.
+pub fn transpose<P: Clone + Default>(image: &Image<P>) -> Image<P> {
+    remap(
+        image,
+        image.height,
+        image.width,
+        swapped_metadata(image),
+        |i, j| (j, i),
+    )
+}
+
+// Builds a `width` by `height` image, taking the pixel at row `i`, column `j` from the
+// position of the source that `source_of` gives.
+fn remap<P: Clone + Default, F: Fn(usize, usize) -> (usize, usize)>(
+    image: &Image<P>,
+    width: usize,
+    height: usize,
+    metadata: Metadata,
+    source_of: F,
+) -> Image<P> {
+    let mut result = Image::new(width, height);
+    result.metadata = metadata;
+
+    for i in 0..height {
+        for j in 0..width {
+            let (y, x) = source_of(i, j);
+            result.pixels[i * width + j] = image.pixels[y * image.width + x].clone();
+        }
+    }
+
+    result
+}
+
+fn swapped_metadata<P>(image: &Image<P>) -> Metadata {
+    let mut metadata = image.metadata.clone();
+
+    if let Some(ref mut dimensions) = metadata.physical_dimensions {
+        mem::swap(
+            &mut dimensions.x_pixels_per_unit,
+            &mut dimensions.y_pixels_per_unit,
+        );
+    }
+
+    metadata
+}
@@ -0,0 +1,123 @@
+extern crate chapter_1;
+
+mod utils;
+
+use chapter_1::filter::{bilateral_cuda, bilateral_cuda_with, AlphaMode, BorderMode, Options};
+use chapter_1::generate;
+use chapter_1::image::{Image, Pixel, Rgba};
+use chapter_1::transform::{crop, pad, Padding};
+
+use utils::compare_images;
+
+// Padded and tiled images stay multiples of the 8 by 8 blocks.
+const RADIUS: usize = 4;
+
+fn options(border: BorderMode) -> Options {
+    Options {
+        border,
+        ..Options::default()
+    }
+}
+
+// Filters a copy padded by the radius, where shrinking the window never kicks in, and crops
+// the padding off again.
+fn filter_padded(image: &Image, padding: &Padding<Pixel>) -> Image {
+    let padded = pad(image, RADIUS, RADIUS, RADIUS, RADIUS, padding);
+    let filtered = bilateral_cuda(&padded, RADIUS, 3.5, 30.0).unwrap();
+
+    crop(&filtered, RADIUS, RADIUS, image.width, image.height)
+}
+
+fn tile(image: &Image) -> Image {
+    let (width, height) = (image.width * 3, image.height * 3);
+    let mut tiled = Image::new(width, height);
+
+    for i in 0..height {
+        for j in 0..width {
+            tiled.pixels[i * width + j] =
+                image.pixels[(i % image.height) * image.width + j % image.width].clone();
+        }
+    }
+
+    tiled
+}
+
+#[test]
+fn should_shrink_by_default() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    compare_images(
+        &bilateral_cuda_with(&input, 5, 3.5, 3.0, options(BorderMode::Shrink)).unwrap(),
+        &reference_output,
+    );
+}
+
+#[test]
+fn should_match_padded_filtering() {
+    let image: Image = generate::photographic(64, 48, 2);
+
+    compare_images(
+        &bilateral_cuda_with(&image, RADIUS, 3.5, 30.0, options(BorderMode::Replicate)).unwrap(),
+        &filter_padded(&image, &Padding::Replicate),
+    );
+    compare_images(
+        &bilateral_cuda_with(&image, RADIUS, 3.5, 30.0, options(BorderMode::Mirror)).unwrap(),
+        &filter_padded(&image, &Padding::Mirror),
+    );
+
+    let constant = Options {
+        border: BorderMode::Constant,
+        border_value: 200.0,
+        ..Options::default()
+    };
+    let padding = Padding::Constant(Pixel {
+        r: 200,
+        g: 200,
+        b: 200,
+    });
+
+    compare_images(
+        &bilateral_cuda_with(&image, RADIUS, 3.5, 30.0, constant).unwrap(),
+        &filter_padded(&image, &padding),
+    );
+}
+
+#[test]
+fn should_match_tiled_filtering() {
+    let image: Image = generate::photographic(64, 48, 3);
+
+    let tiled = bilateral_cuda(&tile(&image), RADIUS, 3.5, 30.0).unwrap();
+
+    compare_images(
+        &bilateral_cuda_with(&image, RADIUS, 3.5, 30.0, options(BorderMode::Wrap)).unwrap(),
+        &crop(&tiled, image.width, image.height, image.width, image.height),
+    );
+}
+
+#[test]
+fn should_preserve_alpha_with_borders() {
+    let image: Image = generate::photographic(32, 32, 4);
+    let mut rgba = Image::<Rgba>::new(32, 32);
+
+    for (index, (target, pixel)) in rgba.pixels.iter_mut().zip(image.pixels.iter()).enumerate() {
+        *target = Rgba {
+            r: pixel.r,
+            g: pixel.g,
+            b: pixel.b,
+            a: (index * 7 % 256) as u8,
+        };
+    }
+
+    let options = Options {
+        alpha: AlphaMode::Preserve,
+        border: BorderMode::Mirror,
+        ..Options::default()
+    };
+
+    let filtered = bilateral_cuda_with(&rgba, RADIUS, 3.5, 30.0, options).unwrap();
+
+    for (lhs, rhs) in filtered.pixels.iter().zip(rgba.pixels.iter()) {
+        assert_eq!(lhs.a, rhs.a);
+    }
+}
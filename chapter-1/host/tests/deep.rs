@@ -0,0 +1,84 @@
+extern crate chapter_1;
+
+use std::env::temp_dir;
+
+use chapter_1::filter::{bilateral_cuda_luma, bilateral_cuda_pixel as filter};
+use chapter_1::image::{Image, Luma, Pixel};
+
+fn widen(source: &Image) -> Image<Pixel<u16>> {
+    let mut image = Image::new(source.width, source.height);
+
+    for (index, pixel) in source.pixels.iter().enumerate() {
+        image.pixels[index] = Pixel {
+            r: pixel.r as u16 * 257,
+            g: pixel.g as u16 * 257,
+            b: pixel.b as u16 * 257,
+        };
+    }
+
+    image
+}
+
+fn compare_narrowed(current: &Image<Pixel<u16>>, reference: &Image) {
+    assert_eq!(current.width, reference.width);
+    assert_eq!(current.height, reference.height);
+
+    let close = |lhs: u16, rhs: u8| ((lhs / 257) as i32 - rhs as i32).abs() <= 1;
+
+    for (lhs, rhs) in current.pixels.iter().zip(reference.pixels.iter()) {
+        assert!(close(lhs.r, rhs.r) && close(lhs.g, rhs.g) && close(lhs.b, rhs.b));
+    }
+}
+
+#[test]
+fn should_save_and_open_16_bit() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let mut image = widen(&input);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.b = pixel.b ^ (index % 256) as u16;
+    }
+
+    let path = temp_dir().join("chapter-1-deep-512.png");
+    image.save(path.to_str().unwrap()).unwrap();
+
+    let reopened = Image::<Pixel<u16>>::open(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(reopened.width, image.width);
+    assert_eq!(reopened.height, image.height);
+    assert!(reopened.pixels == image.pixels);
+}
+
+#[test]
+fn should_match_8_bit_reference_512() {
+    // Range distances grow 257 times, so `sigma_r^2` has to follow them.
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let image = widen(&input);
+    let sigma_r = 3.0 * 257f64.sqrt();
+
+    let current_output = filter(&image, 5, 3.5, sigma_r);
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    compare_narrowed(&current_output.unwrap(), &reference_output);
+}
+
+#[test]
+fn should_keep_16_bit_precision() {
+    let mut image = Image::<Luma<u16>>::new(1024, 8);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = (index % 1024) as u16;
+    }
+
+    let output = bilateral_cuda_luma(&image, 5, 3.5, 3.0).unwrap();
+
+    let mut values = output
+        .pixels
+        .iter()
+        .map(|pixel| pixel.y)
+        .collect::<Vec<_>>();
+    values.sort();
+    values.dedup();
+
+    assert!(values.len() > 256);
+}
@@ -0,0 +1,95 @@
+extern crate chapter_1;
+
+use std::env::temp_dir;
+
+use chapter_1::filter::bilateral_cuda_pixel as filter;
+use chapter_1::image::{Image, Pixel};
+
+fn to_hdr(source: &Image, scale: f32) -> Image<Pixel<f32>> {
+    let mut image = Image::new(source.width, source.height);
+
+    for (index, pixel) in source.pixels.iter().enumerate() {
+        image.pixels[index] = Pixel {
+            r: pixel.r as f32 * scale,
+            g: pixel.g as f32 * scale,
+            b: pixel.b as f32 * scale,
+        };
+    }
+
+    image
+}
+
+fn gradient(width: usize, height: usize) -> Image<Pixel<f32>> {
+    let mut image = Image::new(width, height);
+
+    for i in 0..height {
+        for j in 0..width {
+            // Flat spans give the run length encoder something to do.
+            image.pixels[i * width + j] = Pixel {
+                r: (j / 9) as f32 * 0.37,
+                g: 1000.0 / (1 + i * j) as f32,
+                b: if (i + j) % 5 == 0 {
+                    0.0
+                } else {
+                    1e-3 * j as f32
+                },
+            };
+        }
+    }
+
+    image
+}
+
+#[test]
+fn should_save_and_open_pfm() {
+    let mut image = gradient(33, 17);
+    image.pixels[5].r = -2.5;
+
+    let path = temp_dir().join("chapter-1-hdr.pfm");
+    image.save_hdr(path.to_str().unwrap()).unwrap();
+
+    let reopened = Image::open_hdr(path.to_str().unwrap()).unwrap();
+
+    assert_eq!((reopened.width, reopened.height), (33, 17));
+    assert!(reopened.pixels == image.pixels);
+}
+
+#[test]
+fn should_save_and_open_radiance() {
+    // Narrow images are stored flat, wider ones run length encoded.
+    for &width in &[5, 300] {
+        let image = gradient(width, 12);
+
+        let path = temp_dir().join(format!("chapter-1-hdr-{}.hdr", width));
+        image.save_hdr(path.to_str().unwrap()).unwrap();
+
+        let reopened = Image::open_hdr(path.to_str().unwrap()).unwrap();
+
+        assert_eq!((reopened.width, reopened.height), (width, 12));
+
+        for (lhs, rhs) in reopened.pixels.iter().zip(image.pixels.iter()) {
+            // Channels share the exponent of the largest one, which keeps 8 bits of mantissa.
+            let largest = rhs.r.max(rhs.g).max(rhs.b);
+
+            for &(lhs, rhs) in &[(lhs.r, rhs.r), (lhs.g, rhs.g), (lhs.b, rhs.b)] {
+                assert!((lhs - rhs).abs() <= largest / 128.0);
+            }
+        }
+    }
+}
+
+#[test]
+fn should_match_8_bit_reference_512() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let image = to_hdr(&input, 1.0);
+
+    let reference_output: Image = Image::open("../../fixtures/ref-output-512.png").unwrap();
+    let output = filter(&image, 5, 3.5, 3.0).unwrap();
+
+    // The 8-bit filter truncates what is kept in full here.
+    let below = |lhs: f32, rhs: u8| lhs >= rhs as f32 - 1e-3 && lhs < rhs as f32 + 1.0 + 1e-3;
+
+    for (lhs, rhs) in output.pixels.iter().zip(reference_output.pixels.iter()) {
+        assert!(below(lhs.r, rhs.r) && below(lhs.g, rhs.g) && below(lhs.b, rhs.b));
+    }
+}
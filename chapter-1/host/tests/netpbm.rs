@@ -0,0 +1,147 @@
+extern crate chapter_1;
+
+use std::env::temp_dir;
+use std::fs::File;
+use std::io::{Read, Write};
+
+use chapter_1::image::{Expansion, Image, ImageError, Luma, Pixel, Rgba};
+
+fn temp_path(name: &str) -> String {
+    temp_dir().join(name).to_str().unwrap().to_string()
+}
+
+fn write_file(name: &str, bytes: &[u8]) -> String {
+    let path = temp_path(name);
+    File::create(&path).unwrap().write_all(bytes).unwrap();
+    path
+}
+
+fn magic(path: &str) -> Vec<u8> {
+    let mut bytes = vec![0; 2];
+    File::open(path).unwrap().read_exact(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn should_save_and_open_ppm() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+
+    let path = temp_path("chapter-1-netpbm-512.ppm");
+    image.save(&path).unwrap();
+
+    let reopened = Image::<Pixel>::open(&path).unwrap();
+
+    assert_eq!(magic(&path), b"P6");
+    assert_eq!(reopened.width, image.width);
+    assert_eq!(reopened.height, image.height);
+    assert!(reopened.pixels == image.pixels);
+}
+
+#[test]
+fn should_save_and_open_pgm_16_bit() {
+    let mut image = Image::<Luma<u16>>::new(300, 200);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = (index * 251) as u16;
+    }
+
+    let path = temp_path("chapter-1-netpbm-16.pgm");
+    image.save(&path).unwrap();
+
+    let reopened = Image::<Luma<u16>>::open(&path).unwrap();
+
+    assert_eq!(magic(&path), b"P5");
+    assert!(reopened.pixels == image.pixels);
+}
+
+#[test]
+fn should_save_and_open_pam() {
+    let mut image = Image::<Rgba>::new(64, 32);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.g = index as u8;
+        pixel.a = (index * 7) as u8;
+    }
+
+    let path = temp_path("chapter-1-netpbm-rgba.pam");
+    image.save(&path).unwrap();
+
+    let reopened = Image::<Rgba>::open(&path).unwrap();
+
+    assert_eq!(magic(&path), b"P7");
+    assert!(reopened.pixels == image.pixels);
+}
+
+#[test]
+fn should_pick_format_by_magic_bytes() {
+    let mut bytes = b"P5\n# four levels\n4 1\n3\n".to_vec();
+    bytes.extend_from_slice(&[0, 1, 2, 3]);
+
+    let path = write_file("chapter-1-netpbm-gray.img", &bytes);
+
+    let image = Image::<Luma>::open(&path).unwrap();
+    let levels = image.pixels.iter().map(|pixel| pixel.y).collect::<Vec<_>>();
+
+    assert_eq!(levels, vec![0, 85, 170, 255]);
+
+    match Image::<Luma>::open_with(&path, Expansion::Strict) {
+        Err(ImageError::UnsupportedBitDepth(_)) => {}
+        _ => panic!("Scaled samples must be rejected"),
+    }
+}
+
+#[test]
+fn should_reject_broken_files() {
+    let path = write_file(
+        "chapter-1-netpbm-truncated.ppm",
+        b"P6 4 4 255\n\x00\x01\x02",
+    );
+
+    match Image::<Pixel>::open(&path) {
+        Err(ImageError::TruncatedData) => {}
+        _ => panic!("Truncated file must be rejected"),
+    }
+
+    match Image::<Luma>::open(&path) {
+        Err(ImageError::UnsupportedColorType(_)) => {}
+        _ => panic!("Pixmap must not be opened as a graymap"),
+    }
+
+    let image = Image::<Rgba>::new(8, 8);
+
+    assert!(image.save(&temp_path("chapter-1-netpbm-rgba.ppm")).is_err());
+
+    match Image::<Pixel>::new(8, 8).save(&temp_path("chapter-1-netpbm.jpg")) {
+        Err(ImageError::UnsupportedFormat) => {}
+        _ => panic!("Unknown extensions must be rejected"),
+    }
+}
+
+#[test]
+fn should_round_rescaled_samples() {
+    let mut bytes = b"P5\n3 1\n2\n".to_vec();
+    bytes.extend_from_slice(&[0, 1, 2]);
+
+    let image = Image::<Luma>::from_bytes(&bytes).unwrap();
+    let levels = image.pixels.iter().map(|pixel| pixel.y).collect::<Vec<_>>();
+
+    assert_eq!(levels, vec![0, 128, 255]);
+}
+
+#[test]
+fn should_reject_invalid_maxval() {
+    let headers: [&[u8]; 4] = [
+        b"P5\n1 1\n0\n\x00\x00",
+        b"P5\n1 1\n65536\n\x00\x00",
+        // Wraps around to 255 when cut to 32 bits.
+        b"P5\n1 1\n4294967551\n\x00\x00",
+        b"P7\nWIDTH 1\nHEIGHT 1\nDEPTH 1\nMAXVAL 4294967551\nENDHDR\n\x00\x00",
+    ];
+
+    for bytes in headers.iter() {
+        match Image::<Luma>::from_bytes(bytes) {
+            Err(ImageError::InvalidHeader) => {}
+            _ => panic!("Maximum values out of 16 bits must be rejected"),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+extern crate chapter_1;
+
+mod utils;
+
+use chapter_1::filter::{bilateral_cuda_with, Options, Precision};
+use chapter_1::generate;
+use chapter_1::image::{Image, Pixel};
+use chapter_1::metrics::max_abs_error;
+
+use utils::compare_images;
+
+// Agreed bounds of `Precision::Single` against the f64 reference: one step for 8-bit samples,
+// in under 0.1% of the pixels, and `1e-5` for float samples.
+const MAX_STEPS: f64 = 1.0;
+const MAX_DIFFERING: f64 = 0.001;
+const MAX_FLOAT_ERROR: f64 = 1e-5;
+
+fn options(precision: Precision) -> Options {
+    Options {
+        precision,
+        ..Options::default()
+    }
+}
+
+fn check_against_reference(current_output: &Image, reference_output: &Image) {
+    let differing = current_output
+        .pixels
+        .iter()
+        .zip(&reference_output.pixels)
+        .filter(|&(lhs, rhs)| lhs != rhs)
+        .count();
+
+    assert!(max_abs_error(current_output, reference_output) <= MAX_STEPS);
+    assert!(
+        (differing as f64) < MAX_DIFFERING * current_output.pixels.len() as f64,
+        "{} pixels differ",
+        differing
+    );
+}
+
+#[test]
+fn should_keep_double_precision_by_default() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    assert_eq!(Options::default().precision, Precision::Double);
+    compare_images(
+        &bilateral_cuda_with(&input, 5, 3.5, 3.0, options(Precision::Double)).unwrap(),
+        &reference_output,
+    );
+}
+
+#[test]
+fn should_stay_close_to_reference_512() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    check_against_reference(
+        &bilateral_cuda_with(&input, 5, 3.5, 3.0, options(Precision::Single)).unwrap(),
+        &reference_output,
+    );
+}
+
+#[test]
+fn should_stay_close_to_reference_1024() {
+    let input = Image::open("../../fixtures/input-1024.png").unwrap();
+    let reference_output = Image::open("../../fixtures/ref-output-1024.png").unwrap();
+
+    check_against_reference(
+        &bilateral_cuda_with(&input, 5, 3.5, 3.0, options(Precision::Single)).unwrap(),
+        &reference_output,
+    );
+}
+
+#[test]
+fn should_stay_close_with_float_samples() {
+    let image: Image<Pixel<f32>> = generate::photographic(96, 64, 1);
+
+    for &sigma_r in &[0.01, 0.1, 1.0] {
+        let single = bilateral_cuda_with(&image, 5, 3.5, sigma_r, options(Precision::Single));
+        let double = bilateral_cuda_with(&image, 5, 3.5, sigma_r, options(Precision::Double));
+
+        let error = max_abs_error(&single.unwrap(), &double.unwrap());
+        assert!(error <= MAX_FLOAT_ERROR, "error {}", error);
+    }
+}
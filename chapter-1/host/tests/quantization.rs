@@ -0,0 +1,147 @@
+extern crate chapter_1;
+
+mod utils;
+
+use std::f64;
+
+use chapter_1::filter::{
+    bilateral_cuda_planar, bilateral_cuda_with, BorderMode, Options, Quantization, WindowMode,
+};
+use chapter_1::generate;
+use chapter_1::image::{Image, Luma, Pixel};
+use chapter_1::planar::PlanarImage;
+
+use utils::compare_images;
+
+// Columns of 100, 100 and 102: every 3x3 window of the wrapped image averages to 100 + 2/3.
+fn stripes() -> Image<Luma> {
+    let mut image: Image<Luma> = Image::new(24, 24);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = if index % 3 == 2 { 102 } else { 100 };
+    }
+
+    image
+}
+
+// With infinite sigmas every weight is exactly 1, so the filter is a plain box average.
+fn box_filter(quantization: Quantization, dither_seed: u32) -> Image<Luma> {
+    let options = Options {
+        border: BorderMode::Wrap,
+        window: WindowMode::Symmetric,
+        quantization,
+        dither_seed,
+        ..Options::default()
+    };
+
+    bilateral_cuda_with(&stripes(), 1, f64::INFINITY, f64::INFINITY, options).unwrap()
+}
+
+fn mean<P, F: Fn(&P) -> f64>(image: &Image<P>, sample: F) -> f64 {
+    image.pixels.iter().map(sample).sum::<f64>() / image.pixels.len() as f64
+}
+
+#[test]
+fn should_truncate_by_default() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    let options = Options {
+        quantization: Quantization::Truncate,
+        ..Options::default()
+    };
+
+    assert_eq!(Options::default(), options);
+    compare_images(
+        &bilateral_cuda_with(&input, 5, 3.5, 3.0, options).unwrap(),
+        &reference_output,
+    );
+
+    assert!(box_filter(Quantization::Truncate, 0)
+        .pixels
+        .iter()
+        .all(|pixel| pixel.y == 100));
+}
+
+#[test]
+fn should_round_to_nearest() {
+    assert!(box_filter(Quantization::Round, 0)
+        .pixels
+        .iter()
+        .all(|pixel| pixel.y == 101));
+}
+
+#[test]
+fn should_dither_to_exact_mean() {
+    // 43 of the 64 thresholds are above 1/3, and the image is made of whole 8x8 tiles.
+    for seed in 0..64 {
+        let dithered = box_filter(Quantization::Dither, seed);
+
+        assert!(dithered
+            .pixels
+            .iter()
+            .all(|pixel| pixel.y == 100 || pixel.y == 101));
+        assert_eq!(mean(&dithered, |pixel| pixel.y as f64), 100.0 + 43.0 / 64.0);
+    }
+
+    assert_ne!(
+        box_filter(Quantization::Dither, 5).pixels,
+        box_filter(Quantization::Dither, 6).pixels
+    );
+}
+
+#[test]
+fn should_not_darken() {
+    let image: Image = generate::photographic(128, 96, 2);
+    let filter = |quantization| {
+        let options = Options {
+            quantization,
+            ..Options::default()
+        };
+
+        bilateral_cuda_with(&image, 5, 3.5, 30.0, options).unwrap()
+    };
+
+    let truncated = mean(&filter(Quantization::Truncate), |pixel| pixel.g as f64);
+    let rounded = mean(&filter(Quantization::Round), |pixel| pixel.g as f64);
+
+    assert!(rounded - truncated > 0.45 && rounded - truncated < 0.55);
+}
+
+#[test]
+fn should_keep_float_samples() {
+    let image: Image<Pixel<f32>> = generate::photographic(64, 48, 3);
+    let filter = |quantization| {
+        let options = Options {
+            quantization,
+            ..Options::default()
+        };
+
+        bilateral_cuda_with(&image, 5, 3.5, 0.1, options).unwrap()
+    };
+
+    let truncated = filter(Quantization::Truncate);
+
+    assert_eq!(truncated.pixels, filter(Quantization::Round).pixels);
+    assert_eq!(truncated.pixels, filter(Quantization::Dither).pixels);
+}
+
+#[test]
+fn should_quantize_planar_images() {
+    let planar = PlanarImage::from_image(&stripes());
+
+    for &(quantization, dither_seed) in &[(Quantization::Round, 0), (Quantization::Dither, 5)] {
+        let options = Options {
+            border: BorderMode::Wrap,
+            window: WindowMode::Symmetric,
+            quantization,
+            dither_seed,
+            ..Options::default()
+        };
+
+        let output =
+            bilateral_cuda_planar(&planar, 1, f64::INFINITY, f64::INFINITY, options).unwrap();
+
+        assert!(output.to_image::<Luma>().pixels == box_filter(quantization, dither_seed).pixels);
+    }
+}
@@ -0,0 +1,84 @@
+extern crate chapter_1;
+
+use std::env::temp_dir;
+
+use chapter_1::filter::{bilateral_cuda_rgba as filter, AlphaMode};
+use chapter_1::image::{Image, Rgba};
+
+fn with_alpha<F: Fn(usize, &Rgba) -> u8>(source: &Image, alpha: F) -> Image<Rgba> {
+    let mut image = Image::new(source.width, source.height);
+
+    for (index, pixel) in source.pixels.iter().enumerate() {
+        let mut rgba = Rgba {
+            r: pixel.r,
+            g: pixel.g,
+            b: pixel.b,
+            a: 0,
+        };
+
+        rgba.a = alpha(index, &rgba);
+        image.pixels[index] = rgba;
+    }
+
+    image
+}
+
+fn compare_color(current: &Image<Rgba>, reference: &Image) {
+    assert_eq!(current.width, reference.width);
+    assert_eq!(current.height, reference.height);
+
+    for (lhs, rhs) in current.pixels.iter().zip(reference.pixels.iter()) {
+        assert_eq!((lhs.r, lhs.g, lhs.b), (rhs.r, rhs.g, rhs.b));
+    }
+}
+
+#[test]
+fn should_save_and_open_rgba() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let image = with_alpha(&input, |index, _| (index % 256) as u8);
+
+    let path = temp_dir().join("chapter-1-rgba-512.png");
+    image.save(path.to_str().unwrap()).unwrap();
+
+    let reopened = Image::<Rgba>::open(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(reopened.width, image.width);
+    assert_eq!(reopened.height, image.height);
+    assert!(reopened.pixels == image.pixels);
+}
+
+#[test]
+fn should_reject_rgb_as_rgba() {
+    assert!(Image::<Rgba>::open("../../fixtures/input-512.png").is_err());
+}
+
+#[test]
+fn should_preserve_alpha_512() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let image = with_alpha(&input, |index, _| (index % 251) as u8);
+
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    let current_output = filter(&image, 5, 3.5, 3.0, AlphaMode::Preserve).unwrap();
+    compare_color(&current_output, &reference_output);
+
+    for (lhs, rhs) in current_output.pixels.iter().zip(image.pixels.iter()) {
+        assert_eq!(lhs.a, rhs.a);
+    }
+}
+
+#[test]
+fn should_filter_alpha_512() {
+    // Alpha mirrors the red channel, so it must be filtered exactly like red.
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let image = with_alpha(&input, |_, pixel| pixel.r);
+
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    let current_output = filter(&image, 5, 3.5, 3.0, AlphaMode::Filter).unwrap();
+    compare_color(&current_output, &reference_output);
+
+    for (lhs, rhs) in current_output.pixels.iter().zip(reference_output.pixels.iter()) {
+        assert_eq!(lhs.a, rhs.r);
+    }
+}
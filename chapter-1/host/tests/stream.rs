@@ -0,0 +1,77 @@
+extern crate chapter_1;
+
+use std::env::temp_dir;
+use std::fs::File;
+use std::io::{self, Read};
+
+use chapter_1::image::{Format, Image, ImageError, Luma};
+
+/// Hands out a single byte per `read`, like a slow socket does.
+struct Trickle<'a>(&'a [u8]);
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.0.is_empty() || buffer.is_empty() {
+            return Ok(0);
+        }
+
+        buffer[0] = self.0[0];
+        self.0 = &self.0[1..];
+        Ok(1)
+    }
+}
+
+#[test]
+fn should_encode_and_decode_bytes() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+
+    for format in &[Format::Png, Format::Ppm, Format::Pam] {
+        let bytes = image.to_bytes(*format).unwrap();
+        let decoded: Image = Image::from_bytes(&bytes).unwrap();
+
+        assert_eq!(Format::from_magic(&bytes), Some(*format));
+        assert!(decoded.pixels == image.pixels);
+    }
+}
+
+#[test]
+fn should_match_file_api() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+
+    let path = temp_dir().join("chapter-1-stream-512.png");
+    image.save(path.to_str().unwrap()).unwrap();
+
+    let mut saved = Vec::new();
+    File::open(&path).unwrap().read_to_end(&mut saved).unwrap();
+
+    assert!(saved == image.to_bytes(Format::Png).unwrap());
+}
+
+#[test]
+fn should_read_slow_streams() {
+    let mut image = Image::<Luma>::new(40, 30);
+
+    for (index, pixel) in image.pixels.iter_mut().enumerate() {
+        pixel.y = (index % 256) as u8;
+    }
+
+    for format in &[Format::Png, Format::Pgm] {
+        let bytes = image.to_bytes(*format).unwrap();
+        let decoded = Image::<Luma>::read(Trickle(&bytes)).unwrap();
+
+        assert!(decoded.pixels == image.pixels);
+    }
+}
+
+#[test]
+fn should_reject_unknown_streams() {
+    match Image::<Luma>::from_bytes(b"GIF89a") {
+        Err(ImageError::UnsupportedFormat) => {}
+        _ => panic!("GIF must be rejected"),
+    }
+
+    match Image::<Luma>::from_bytes(b"P5") {
+        Err(ImageError::TruncatedData) => {}
+        _ => panic!("Empty graymap must be rejected"),
+    }
+}
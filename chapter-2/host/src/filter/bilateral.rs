@@ -1,24 +1,205 @@
-use image::Pixel;
+use filter::Options;
+use image::{Luma, Pixel, Rgba};
 
 cuda_kernel! {
-    fn bilateral_kernel(src: *const Pixel, dst: *mut Pixel, radius: u32, sigma_d: f64, sigma_r: f64) {
-        self::device::bilateral_kernel(src, dst, radius, sigma_d, sigma_r);
+    fn bilateral_kernel(
+        src: *const Pixel,
+        dst: *mut Pixel,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel(src, dst, radius, sigma_d, sigma_r, options);
+    }
+}
+
+cuda_kernel! {
+    fn bilateral_kernel_16(
+        src: *const Pixel<u16>,
+        dst: *mut Pixel<u16>,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel(src, dst, radius, sigma_d, sigma_r, options);
+    }
+}
+
+cuda_kernel! {
+    fn bilateral_kernel_f32(
+        src: *const Pixel<f32>,
+        dst: *mut Pixel<f32>,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel(src, dst, radius, sigma_d, sigma_r, options);
+    }
+}
+
+cuda_kernel! {
+    fn bilateral_kernel_rgba(
+        src: *const Rgba,
+        dst: *mut Rgba,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel(src, dst, radius, sigma_d, sigma_r, options);
+    }
+}
+
+cuda_kernel! {
+    fn bilateral_kernel_rgba_16(
+        src: *const Rgba<u16>,
+        dst: *mut Rgba<u16>,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel(src, dst, radius, sigma_d, sigma_r, options);
+    }
+}
+
+cuda_kernel! {
+    fn bilateral_kernel_rgba_f32(
+        src: *const Rgba<f32>,
+        dst: *mut Rgba<f32>,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel(src, dst, radius, sigma_d, sigma_r, options);
+    }
+}
+
+cuda_kernel! {
+    fn bilateral_kernel_luma(
+        src: *const Luma,
+        dst: *mut Luma,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel(src, dst, radius, sigma_d, sigma_r, options);
+    }
+}
+
+cuda_kernel! {
+    fn bilateral_kernel_luma_16(
+        src: *const Luma<u16>,
+        dst: *mut Luma<u16>,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel(src, dst, radius, sigma_d, sigma_r, options);
+    }
+}
+
+cuda_kernel! {
+    fn bilateral_kernel_luma_f32(
+        src: *const Luma<f32>,
+        dst: *mut Luma<f32>,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel(src, dst, radius, sigma_d, sigma_r, options);
+    }
+}
+
+cuda_kernel! {
+    fn bilateral_kernel_planar(
+        src: *const u8,
+        dst: *mut u8,
+        channels: u32,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel_planar(
+            src, dst, channels, radius, sigma_d, sigma_r, options,
+        );
+    }
+}
+
+cuda_kernel! {
+    fn bilateral_kernel_planar_16(
+        src: *const u16,
+        dst: *mut u16,
+        channels: u32,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel_planar(
+            src, dst, channels, radius, sigma_d, sigma_r, options,
+        );
+    }
+}
+
+cuda_kernel! {
+    fn bilateral_kernel_planar_f32(
+        src: *const f32,
+        dst: *mut f32,
+        channels: u32,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options
+    ) {
+        self::device::bilateral_kernel_planar(
+            src, dst, channels, radius, sigma_d, sigma_r, options,
+        );
     }
 }
 
 #[cfg(target_os = "cuda")]
 mod device {
-    use core::cmp::{max, min};
-    use image::Pixel;
-    use math::{exp, sqrt};
+    use core::cmp::min;
+    use core::ops::{Add, Div, Mul, Neg, Sub};
+    use filter::{AlphaMode, BorderMode, Options, Precision};
+    use image::{GenericPixel, Sample};
+    use math::{exp, expf, sqrt, sqrtf};
     use nvptx_builtins::*;
 
-    pub unsafe fn bilateral_kernel(
-        src: *const Pixel,
-        dst: *mut Pixel,
+    pub unsafe fn bilateral_kernel<P: GenericPixel>(
+        src: *const P,
+        dst: *mut P,
         radius: u32,
         sigma_d: f64,
         sigma_r: f64,
+        options: Options,
+    ) {
+        match options.precision {
+            Precision::Double => {
+                bilateral_kernel_in::<P, f64>(src, dst, radius, sigma_d, sigma_r, options)
+            }
+            Precision::Single => {
+                bilateral_kernel_in::<P, f32>(src, dst, radius, sigma_d, sigma_r, options)
+            }
+        }
+    }
+
+    unsafe fn bilateral_kernel_in<P: GenericPixel, F: Real>(
+        src: *const P,
+        dst: *mut P,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options,
     ) {
         let width = grid_dim_x() * block_dim_x();
         let height = grid_dim_y() * block_dim_y();
@@ -31,27 +212,36 @@ mod device {
             width: width as i32,
         };
 
-        let mut r_value: f64 = 0.0;
-        let mut r_accum: f64 = 0.0;
+        let center = src_image.pixel(i, j);
+        let (sigma_d, sigma_r) = (F::from_f64(sigma_d), F::from_f64(sigma_r));
+        let mut constant = P::default();
 
-        let mut g_value: f64 = 0.0;
-        let mut g_accum: f64 = 0.0;
+        for channel in 0..P::CHANNELS {
+            constant.set_channel(channel, P::Sample::from_f64(options.border_value));
+        }
 
-        let mut b_value: f64 = 0.0;
-        let mut b_accum: f64 = 0.0;
+        // Formats have at most 4 channels.
+        let mut values = [F::from_f64(0.0); 4];
+        let mut accum = F::from_f64(0.0);
 
-        for k in max(i - radius as i32, 0)..min(i + radius as i32, height as i32) {
-            for l in max(j - radius as i32, 0)..min(j + radius as i32, width as i32) {
-                let w = w_kernel(&src_image, i, j, k, l, sigma_d, sigma_r);
+        for k in options.window.range(i, radius as i32) {
+            for l in options.window.range(j, radius as i32) {
+                let pixel = match (
+                    options.border.locate(k, height as i32),
+                    options.border.locate(l, width as i32),
+                ) {
+                    (Some(y), Some(x)) => src_image.pixel(y, x),
+                    _ if options.border == BorderMode::Constant => &constant,
+                    _ => continue,
+                };
 
-                r_value = r_value + w * src_image.pixel(k, l).r as f64;
-                r_accum = r_accum + w;
+                let w: F = w_kernel(i, j, k, l, center, pixel, sigma_d, sigma_r);
 
-                g_value = g_value + w * src_image.pixel(k, l).g as f64;
-                g_accum = g_accum + w;
+                for (channel, value) in values.iter_mut().take(P::CHANNELS).enumerate() {
+                    *value = *value + w * F::from_f64(pixel.channel(channel).to_f64());
+                }
 
-                b_value = b_value + w * src_image.pixel(k, l).b as f64;
-                b_accum = b_accum + w;
+                accum = accum + w;
             }
         }
 
@@ -60,62 +250,263 @@ mod device {
             width: width as i32,
         };
 
-        dst_image.mut_pixel(i, j).r = (r_value / r_accum) as u8;
-        dst_image.mut_pixel(i, j).g = (g_value / g_accum) as u8;
-        dst_image.mut_pixel(i, j).b = (b_value / b_accum) as u8;
+        for (channel, value) in values.iter().take(P::CHANNELS).enumerate() {
+            let sample = match options.alpha {
+                AlphaMode::Preserve if channel >= P::COLOR_CHANNELS => center.channel(channel),
+                _ => options.quantize((*value / accum).to_f64(), i, j),
+            };
+
+            dst_image.mut_pixel(i, j).set_channel(channel, sample);
+        }
     }
 
-    struct Image {
-        pixels: *const Pixel,
+    pub unsafe fn bilateral_kernel_planar<T: Sample>(
+        src: *const T,
+        dst: *mut T,
+        channels: u32,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options,
+    ) {
+        match options.precision {
+            Precision::Double => bilateral_kernel_planar_in::<T, f64>(
+                src, dst, channels, radius, sigma_d, sigma_r, options,
+            ),
+            Precision::Single => bilateral_kernel_planar_in::<T, f32>(
+                src, dst, channels, radius, sigma_d, sigma_r, options,
+            ),
+        }
+    }
+
+    unsafe fn bilateral_kernel_planar_in<T: Sample, F: Real>(
+        src: *const T,
+        dst: *mut T,
+        channels: u32,
+        radius: u32,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options,
+    ) {
+        let width = grid_dim_x() * block_dim_x();
+        let height = grid_dim_y() * block_dim_y();
+
+        let j = (block_dim_x() * block_idx_x() + thread_idx_x()) as i32;
+        let i = (block_dim_y() * block_idx_y() + thread_idx_y()) as i32;
+
+        let src_planes = Planes {
+            samples: src,
+            width: width as i32,
+            size: (width * height) as isize,
+            channels: min(channels, 4) as usize,
+            constant: T::from_f64(options.border_value).to_f64(),
+        };
+
+        let (sigma_d, sigma_r) = (F::from_f64(sigma_d), F::from_f64(sigma_r));
+
+        // Every thread of a warp reads neighbouring samples of the same plane.
+        let mut values = [F::from_f64(0.0); 4];
+        let mut accum = F::from_f64(0.0);
+
+        for k in options.window.range(i, radius as i32) {
+            for l in options.window.range(j, radius as i32) {
+                let neighbour = match (
+                    options.border.locate(k, height as i32),
+                    options.border.locate(l, width as i32),
+                ) {
+                    (Some(y), Some(x)) => Some((y, x)),
+                    _ if options.border == BorderMode::Constant => None,
+                    _ => continue,
+                };
+
+                let w: F = w_kernel_planar(&src_planes, i, j, k, l, neighbour, sigma_d, sigma_r);
+
+                for (channel, value) in values.iter_mut().take(src_planes.channels).enumerate() {
+                    *value = *value + w * F::from_f64(src_planes.value(channel, neighbour));
+                }
+
+                accum = accum + w;
+            }
+        }
+
+        for (channel, value) in values.iter().take(src_planes.channels).enumerate() {
+            let sample = options.quantize((*value / accum).to_f64(), i, j);
+            *dst.offset(src_planes.index(channel, i, j)) = sample;
+        }
+    }
+
+    struct Image<P> {
+        pixels: *const P,
         width: i32,
     }
 
-    struct MutImage {
-        pixels: *mut Pixel,
+    struct MutImage<P> {
+        pixels: *mut P,
         width: i32,
     }
 
-    impl Image {
+    impl<P> Image<P> {
         fn offset(&self, i: i32, j: i32) -> isize {
             (i * self.width + j) as isize
         }
 
-        unsafe fn pixel(&self, i: i32, j: i32) -> &Pixel {
+        unsafe fn pixel(&self, i: i32, j: i32) -> &P {
             &*self.pixels.offset(self.offset(i, j))
         }
     }
 
-    impl MutImage {
+    impl<P> MutImage<P> {
         fn offset(&self, i: i32, j: i32) -> isize {
             (i * self.width + j) as isize
         }
 
-        unsafe fn mut_pixel(&mut self, i: i32, j: i32) -> &mut Pixel {
+        unsafe fn mut_pixel(&mut self, i: i32, j: i32) -> &mut P {
             &mut *self.pixels.offset(self.offset(i, j))
         }
     }
 
-    unsafe fn w_kernel(
-        source: &Image,
+    unsafe fn w_kernel<P: GenericPixel, F: Real>(
         i: i32,
         j: i32,
         k: i32,
         l: i32,
-        sigma_d: f64,
-        sigma_r: f64,
-    ) -> f64 {
-        let w_d = ((i - k) * (i - k) + (j - l) * (j - l)) as f64;
-        let w_r = l2_distance(source.pixel(i, j), source.pixel(k, l));
+        lhs: &P,
+        rhs: &P,
+        sigma_d: F,
+        sigma_r: F,
+    ) -> F {
+        let two = F::from_f64(2.0);
+        let w_d = F::from_f64(((i - k) * (i - k) + (j - l) * (j - l)) as f64);
+        let w_r: F = l2_distance(lhs, rhs);
+
+        (-w_d / (two * sigma_d * sigma_d) - w_r / (two * sigma_r * sigma_r)).exp()
+    }
+
+    // Only the color channels take part in the range distance: alpha is coverage, not color.
+    unsafe fn l2_distance<P: GenericPixel, F: Real>(lhs: &P, rhs: &P) -> F {
+        let mut distance = F::from_f64(0.0);
+
+        for channel in 0..P::COLOR_CHANNELS {
+            let channel_distance = F::from_f64(lhs.channel(channel).to_f64())
+                - F::from_f64(rhs.channel(channel).to_f64());
+            distance = distance + channel_distance * channel_distance;
+        }
+
+        distance.sqrt()
+    }
+
+    // Arithmetic the kernel needs from `f32` and `f64`, so that one body serves both precisions.
+    trait Real:
+        Copy
+        + Add<Output = Self>
+        + Sub<Output = Self>
+        + Mul<Output = Self>
+        + Div<Output = Self>
+        + Neg<Output = Self>
+    {
+        fn from_f64(value: f64) -> Self;
+        fn to_f64(self) -> f64;
+        fn exp(self) -> Self;
+        fn sqrt(self) -> Self;
+    }
+
+    impl Real for f64 {
+        fn from_f64(value: f64) -> Self {
+            value
+        }
+
+        fn to_f64(self) -> f64 {
+            self
+        }
+
+        fn exp(self) -> Self {
+            exp(self)
+        }
 
-        exp(-w_d / (2.0 * sigma_d * sigma_d) - w_r / (2.0 * sigma_r * sigma_r))
+        fn sqrt(self) -> Self {
+            sqrt(self)
+        }
     }
 
-    unsafe fn l2_distance(lhs: &Pixel, rhs: &Pixel) -> f64 {
-        let r_distance = lhs.r as f64 - rhs.r as f64;
-        let g_distance = lhs.g as f64 - rhs.g as f64;
-        let b_distance = lhs.b as f64 - rhs.b as f64;
+    impl Real for f32 {
+        fn from_f64(value: f64) -> Self {
+            value as f32
+        }
+
+        fn to_f64(self) -> f64 {
+            self as f64
+        }
 
-        sqrt(r_distance * r_distance + g_distance * g_distance + b_distance * b_distance)
+        fn exp(self) -> Self {
+            expf(self)
+        }
+
+        fn sqrt(self) -> Self {
+            sqrtf(self)
+        }
+    }
+
+    struct Planes<T> {
+        samples: *const T,
+        width: i32,
+        size: isize,
+        channels: usize,
+        // Value of every sample past the edges with `BorderMode::Constant`.
+        constant: f64,
+    }
+
+    impl<T: Sample> Planes<T> {
+        fn index(&self, channel: usize, i: i32, j: i32) -> isize {
+            channel as isize * self.size + (i * self.width + j) as isize
+        }
+
+        unsafe fn sample(&self, channel: usize, i: i32, j: i32) -> &T {
+            &*self.samples.offset(self.index(channel, i, j))
+        }
+
+        // Sample at `position`, `None` standing for the pixels past the edges.
+        unsafe fn value(&self, channel: usize, position: Option<(i32, i32)>) -> f64 {
+            match position {
+                Some((i, j)) => self.sample(channel, i, j).to_f64(),
+                None => self.constant,
+            }
+        }
+    }
+
+    unsafe fn w_kernel_planar<T: Sample, F: Real>(
+        source: &Planes<T>,
+        i: i32,
+        j: i32,
+        k: i32,
+        l: i32,
+        neighbour: Option<(i32, i32)>,
+        sigma_d: F,
+        sigma_r: F,
+    ) -> F {
+        let two = F::from_f64(2.0);
+        let w_d = F::from_f64(((i - k) * (i - k) + (j - l) * (j - l)) as f64);
+        let w_r: F = l2_distance_planar(source, i, j, neighbour);
+
+        (-w_d / (two * sigma_d * sigma_d) - w_r / (two * sigma_r * sigma_r)).exp()
+    }
+
+    // A fourth plane is alpha, which stays out of the range distance as it does for `Rgba`.
+    unsafe fn l2_distance_planar<T: Sample, F: Real>(
+        source: &Planes<T>,
+        i: i32,
+        j: i32,
+        neighbour: Option<(i32, i32)>,
+    ) -> F {
+        let channels = source.channels.min(3);
+        let mut distance = F::from_f64(0.0);
+
+        for channel in 0..channels {
+            let channel_distance = F::from_f64(source.value(channel, Some((i, j))))
+                - F::from_f64(source.value(channel, neighbour));
+            distance = distance + channel_distance * channel_distance;
+        }
+
+        distance.sqrt()
     }
 }
 
@@ -123,11 +514,69 @@ mod device {
 pub mod host {
     use cuda::driver;
     use cuda::driver::{Block, Direction, Error as CudaError, Grid};
-    use std::mem::size_of;
 
-    use image::{Image, Pixel};
+    use filter::{AlphaMode, Options};
+    use image::{GenericPixel, Image, Luma, Pixel, Plain, Rgba, Sample};
+    use planar::PlanarImage;
     use static_cuda::prelude::*;
-    use static_cuda::{CUDA_CTX, CUDA_MODULE};
+    use static_cuda::{KernelPlaceholder, CUDA_CTX, CUDA_MODULE};
+
+    /// Sample types the device has kernels for.
+    pub trait CudaSample: Sample + Plain {
+        type Kernel: KernelPlaceholder<
+            Args = (*const Pixel<Self>, *mut Pixel<Self>, u32, f64, f64, Options),
+        >;
+
+        type KernelRgba: KernelPlaceholder<
+            Args = (*const Rgba<Self>, *mut Rgba<Self>, u32, f64, f64, Options),
+        >;
+
+        type KernelLuma: KernelPlaceholder<
+            Args = (*const Luma<Self>, *mut Luma<Self>, u32, f64, f64, Options),
+        >;
+
+        type KernelPlanar: KernelPlaceholder<
+            Args = (*const Self, *mut Self, u32, u32, f64, f64, Options),
+        >;
+    }
+
+    impl CudaSample for u8 {
+        type Kernel = super::bilateral_kernel;
+        type KernelRgba = super::bilateral_kernel_rgba;
+        type KernelLuma = super::bilateral_kernel_luma;
+        type KernelPlanar = super::bilateral_kernel_planar;
+    }
+
+    impl CudaSample for u16 {
+        type Kernel = super::bilateral_kernel_16;
+        type KernelRgba = super::bilateral_kernel_rgba_16;
+        type KernelLuma = super::bilateral_kernel_luma_16;
+        type KernelPlanar = super::bilateral_kernel_planar_16;
+    }
+
+    impl CudaSample for f32 {
+        type Kernel = super::bilateral_kernel_f32;
+        type KernelRgba = super::bilateral_kernel_rgba_f32;
+        type KernelLuma = super::bilateral_kernel_luma_f32;
+        type KernelPlanar = super::bilateral_kernel_planar_f32;
+    }
+
+    /// Pixel formats the device has kernels for.
+    pub trait CudaPixel: GenericPixel + Plain {
+        type Kernel: KernelPlaceholder<Args = (*const Self, *mut Self, u32, f64, f64, Options)>;
+    }
+
+    impl<T: CudaSample> CudaPixel for Pixel<T> {
+        type Kernel = T::Kernel;
+    }
+
+    impl<T: CudaSample> CudaPixel for Rgba<T> {
+        type Kernel = T::KernelRgba;
+    }
+
+    impl<T: CudaSample> CudaPixel for Luma<T> {
+        type Kernel = T::KernelLuma;
+    }
 
     pub fn filter(
         source: &Image,
@@ -135,26 +584,136 @@ pub mod host {
         sigma_d: f64,
         sigma_r: f64,
     ) -> Result<Image, CudaError> {
+        filter_pixel(source, radius, sigma_d, sigma_r)
+    }
+
+    /// Filters `Pixel` images of any sample type the device has a kernel for.
+    pub fn filter_pixel<T: CudaSample>(
+        source: &Image<Pixel<T>>,
+        radius: usize,
+        sigma_d: f64,
+        sigma_r: f64,
+    ) -> Result<Image<Pixel<T>>, CudaError> {
+        filter_with(source, radius, sigma_d, sigma_r, Options::default())
+    }
+
+    pub fn filter_rgba<T: CudaSample>(
+        source: &Image<Rgba<T>>,
+        radius: usize,
+        sigma_d: f64,
+        sigma_r: f64,
+        alpha: AlphaMode,
+    ) -> Result<Image<Rgba<T>>, CudaError> {
+        let options = Options {
+            alpha,
+            ..Options::default()
+        };
+
+        filter_with(source, radius, sigma_d, sigma_r, options)
+    }
+
+    pub fn filter_luma<T: CudaSample>(
+        source: &Image<Luma<T>>,
+        radius: usize,
+        sigma_d: f64,
+        sigma_r: f64,
+    ) -> Result<Image<Luma<T>>, CudaError> {
+        filter_with(source, radius, sigma_d, sigma_r, Options::default())
+    }
+
+    /// Filters any pixel format the device has a kernel for, with every behaviour set through
+    /// `options`.
+    pub fn filter_with<P: CudaPixel>(
+        source: &Image<P>,
+        radius: usize,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options,
+    ) -> Result<Image<P>, CudaError> {
         let mut destination = Image::new(source.width, source.height);
-        let kernel = CUDA_MODULE.kernel::<super::bilateral_kernel>()?;
+        destination.metadata = source.metadata.clone();
+        let kernel = CUDA_MODULE.kernel::<P::Kernel>()?;
 
         CUDA_CTX.set_current()?;
 
         let d_src = unsafe {
-            let size = source.pixels.len() * size_of::<Pixel>();
-            driver::allocate(size)? as *const Pixel
+            let size = source.as_bytes().len();
+            driver::allocate(size)? as *const P
+        };
+
+        let d_dst = unsafe {
+            let size = destination.as_bytes().len();
+            driver::allocate(size)? as *mut P
         };
 
+        unsafe {
+            driver::copy(
+                source.as_bytes().as_ptr(),
+                d_src as *mut u8,
+                source.as_bytes().len(),
+                Direction::HostToDevice,
+            )?;
+        }
+
+        kernel.execute(
+            Grid::xy(source.width as u32 / 8, source.height as u32 / 8),
+            Block::xy(8, 8),
+            d_src,
+            d_dst,
+            radius as u32,
+            sigma_d,
+            sigma_r,
+            options,
+        )?;
+
+        unsafe {
+            driver::copy(
+                d_dst as *mut u8,
+                destination.as_bytes_mut().as_mut_ptr(),
+                destination.as_bytes().len(),
+                Direction::DeviceToHost,
+            )?;
+
+            driver::deallocate(d_src as *mut u8)?;
+            driver::deallocate(d_dst as *mut u8)?;
+        }
+
+        Ok(destination)
+    }
+
+    /// Filters a planar image of up to 4 channels, comparing pixels over all of them but the
+    /// fourth, which is alpha like in `Rgba` images.
+    ///
+    /// `options.alpha` doesn't apply: alpha planes are always filtered.
+    pub fn filter_planar<T: CudaSample>(
+        source: &PlanarImage<T>,
+        radius: usize,
+        sigma_d: f64,
+        sigma_r: f64,
+        options: Options,
+    ) -> Result<PlanarImage<T>, CudaError> {
+        assert!(source.channels <= 4, "too many channels for the kernel");
+
+        let mut destination = PlanarImage::new(source.width, source.height, source.channels);
+        destination.metadata = source.metadata.clone();
+        let kernel = CUDA_MODULE.kernel::<T::KernelPlanar>()?;
+
+        CUDA_CTX.set_current()?;
+
+        let d_src = unsafe {
+            let size = T::as_bytes(&source.samples).len();
+            driver::allocate(size)? as *const T
+        };
         let d_dst = unsafe {
-            let size = destination.pixels.len() * size_of::<Pixel>();
-            driver::allocate(size)? as *mut Pixel
+            let size = T::as_bytes(&destination.samples).len();
+            driver::allocate(size)? as *mut T
         };
 
         unsafe {
             driver::copy(
-                source.pixels.as_ptr(),
-                d_src as *mut Pixel,
-                source.pixels.len(),
+                T::as_bytes(&source.samples).as_ptr(),
+                d_src as *mut u8,
+                T::as_bytes(&source.samples).len(),
                 Direction::HostToDevice,
             )?;
         }
@@ -164,16 +723,18 @@ pub mod host {
             Block::xy(8, 8),
             d_src,
             d_dst,
+            source.channels as u32,
             radius as u32,
             sigma_d,
             sigma_r,
+            options,
         )?;
 
         unsafe {
             driver::copy(
-                d_dst as *mut Pixel,
-                destination.pixels.as_mut_ptr(),
-                destination.pixels.len(),
+                d_dst as *mut u8,
+                T::as_bytes_mut(&mut destination.samples).as_mut_ptr(),
+                T::as_bytes(&destination.samples).len(),
                 Direction::DeviceToHost,
             )?;
 
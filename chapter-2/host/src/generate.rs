@@ -0,0 +1,150 @@
+use image::{Image, Pixel, Sample};
+
+/// Seeded SplitMix64 generator: the same seed gives the same images on every platform.
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+
+    /// Uniform value in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Red grows to the right, green downwards and blue along the diagonal.
+pub fn gradient<T: Sample>(width: usize, height: usize) -> Image<Pixel<T>> {
+    from_fn(width, height, |x, y| [x, y, (x + y) / 2.0])
+}
+
+/// Black and white squares of `cell` pixels, white in the top left corner.
+///
+/// Panics if `cell` is zero.
+pub fn checkerboard<T: Sample>(width: usize, height: usize, cell: usize) -> Image<Pixel<T>> {
+    assert!(
+        cell > 0,
+        "checkerboard cells must be at least one pixel wide"
+    );
+
+    let mut image = Image::new(width, height);
+
+    for i in 0..height {
+        for j in 0..width {
+            let value = if (i / cell + j / cell) & 1 == 0 {
+                1.0
+            } else {
+                0.0
+            };
+            image.pixels[i * width + j] = pixel([value, value, value]);
+        }
+    }
+
+    image
+}
+
+/// Dark left half and light right half, for checking that edges survive filtering.
+pub fn step_edge<T: Sample>(width: usize, height: usize) -> Image<Pixel<T>> {
+    let mut image = Image::new(width, height);
+
+    for i in 0..height {
+        for j in 0..width {
+            let value = if j < width / 2 { 0.2 } else { 0.8 };
+            image.pixels[i * width + j] = pixel([value, value, value]);
+        }
+    }
+
+    image
+}
+
+/// Every sample drawn uniformly from the full range.
+pub fn noise<T: Sample>(width: usize, height: usize, seed: u64) -> Image<Pixel<T>> {
+    let mut random = Random::new(seed);
+    let mut image = Image::new(width, height);
+
+    for target in &mut image.pixels {
+        *target = pixel([random.next_f64(), random.next_f64(), random.next_f64()]);
+    }
+
+    image
+}
+
+/// Soft-edged discs over a sky-like gradient, with fine texture and a little sensor noise.
+///
+/// Stands in for photographs: large flat areas, edges of all contrasts and some detail.
+pub fn photographic<T: Sample>(width: usize, height: usize, seed: u64) -> Image<Pixel<T>> {
+    let mut random = Random::new(seed);
+
+    let discs: Vec<_> = (0..12)
+        .map(|_| {
+            let center = (random.next_f64(), random.next_f64());
+            let radius = 0.05 + 0.2 * random.next_f64();
+            let color = [random.next_f64(), random.next_f64(), random.next_f64()];
+
+            (center, radius, color)
+        })
+        .collect();
+
+    from_fn(width, height, |x, y| {
+        let mut color = [0.3 + 0.4 * y, 0.4 + 0.3 * y, 0.8 - 0.3 * y];
+
+        for &((center_x, center_y), radius, disc_color) in &discs {
+            let distance =
+                ((x - center_x) * (x - center_x) + (y - center_y) * (y - center_y)).sqrt();
+            let coverage = ((1.0 - distance / radius) * 8.0).clamp(0.0, 1.0);
+
+            for (channel, value) in color.iter_mut().enumerate() {
+                *value = *value * (1.0 - coverage) + disc_color[channel] * coverage;
+            }
+        }
+
+        let texture = 0.03 * (x * 120.0).sin() * (y * 90.0).sin();
+
+        for value in &mut color {
+            *value += texture + 0.02 * (random.next_f64() - 0.5);
+        }
+
+        color
+    })
+}
+
+// Position is given in `[0, 1]`, along the longer side so that circles stay round.
+fn from_fn<T: Sample, F: FnMut(f64, f64) -> [f64; 3]>(
+    width: usize,
+    height: usize,
+    mut function: F,
+) -> Image<Pixel<T>> {
+    let scale = (width.max(height).max(2) - 1) as f64;
+    let mut image = Image::new(width, height);
+
+    for i in 0..height {
+        for j in 0..width {
+            image.pixels[i * width + j] = pixel(function(j as f64 / scale, i as f64 / scale));
+        }
+    }
+
+    image
+}
+
+fn pixel<T: Sample>(values: [f64; 3]) -> Pixel<T> {
+    let sample = |value: f64| T::from_f64(value.clamp(0.0, 1.0) * T::WHITE);
+
+    Pixel {
+        r: sample(values[0]),
+        g: sample(values[1]),
+        b: sample(values[2]),
+    }
+}
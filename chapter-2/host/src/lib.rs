@@ -9,6 +9,8 @@ extern crate lazy_static;
 #[cfg(not(target_os = "cuda"))]
 extern crate cuda;
 #[cfg(not(target_os = "cuda"))]
+extern crate deflate;
+#[cfg(not(target_os = "cuda"))]
 extern crate png;
 
 #[cfg(target_os = "cuda")]
@@ -27,7 +29,7 @@ macro_rules! cuda_kernel {
         $body:block
     ) => {
         #[allow(non_camel_case_types)]
-        struct $name;
+        pub struct $name;
 
         impl $crate::static_cuda::KernelPlaceholder for $name {
             type Args = ($arg0_type $(, $arg_type)*);
@@ -49,3 +51,18 @@ macro_rules! cuda_kernel {
 
 pub mod filter;
 pub mod image;
+
+#[cfg(not(target_os = "cuda"))]
+pub mod generate;
+
+#[cfg(not(target_os = "cuda"))]
+pub mod metrics;
+
+#[cfg(not(target_os = "cuda"))]
+pub mod noise;
+
+#[cfg(not(target_os = "cuda"))]
+pub mod planar;
+
+#[cfg(not(target_os = "cuda"))]
+pub mod transform;
@@ -0,0 +1,70 @@
+use image::{GenericPixel, Image, Metadata, Sample};
+
+/// Image stored channel by channel: every channel is a contiguous plane of samples.
+///
+/// Filters reading a single channel of neighbouring pixels touch adjacent memory this way,
+/// unlike with the interleaved pixels of `Image`.
+pub struct PlanarImage<T = u8> {
+    /// Planes one after another, `width * height` samples each.
+    pub samples: Vec<T>,
+    pub channels: usize,
+    pub width: usize,
+    pub height: usize,
+    pub metadata: Metadata,
+}
+
+impl<T: Sample> PlanarImage<T> {
+    pub fn new(width: usize, height: usize, channels: usize) -> Self {
+        PlanarImage {
+            samples: vec![T::default(); width * height * channels],
+            channels,
+            width,
+            height,
+            metadata: Metadata::default(),
+        }
+    }
+
+    pub fn plane(&self, channel: usize) -> &[T] {
+        let size = self.width * self.height;
+        &self.samples[channel * size..(channel + 1) * size]
+    }
+
+    pub fn plane_mut(&mut self, channel: usize) -> &mut [T] {
+        let size = self.width * self.height;
+        &mut self.samples[channel * size..(channel + 1) * size]
+    }
+
+    /// Scatters every channel of the pixels into a plane of its own.
+    pub fn from_image<P: GenericPixel<Sample = T>>(image: &Image<P>) -> Self {
+        let mut planar = PlanarImage::new(image.width, image.height, P::CHANNELS);
+        planar.metadata = image.metadata.clone();
+
+        for channel in 0..P::CHANNELS {
+            for (sample, pixel) in planar.plane_mut(channel).iter_mut().zip(&image.pixels) {
+                *sample = pixel.channel(channel);
+            }
+        }
+
+        planar
+    }
+
+    /// Gathers pixels back from the planes.
+    pub fn to_image<P: GenericPixel<Sample = T>>(&self) -> Image<P> {
+        assert_eq!(
+            self.channels,
+            P::CHANNELS,
+            "channel count differs from the pixel format"
+        );
+
+        let mut image: Image<P> = Image::new(self.width, self.height);
+        image.metadata = self.metadata.clone();
+
+        for channel in 0..P::CHANNELS {
+            for (pixel, sample) in image.pixels.iter_mut().zip(self.plane(channel)) {
+                pixel.set_channel(channel, *sample);
+            }
+        }
+
+        image
+    }
+}
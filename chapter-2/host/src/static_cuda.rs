@@ -86,10 +86,11 @@ impl<F: KernelPlaceholder> Kernel<F> {
 }
 
 pub mod prelude {
-    pub use super::ModuleKernelWithArity5;
+    pub use super::ModuleKernelWithArity6;
+    pub use super::ModuleKernelWithArity7;
 }
 
-pub trait ModuleKernelWithArity5<I1, I2, I3, I4, I5> {
+pub trait ModuleKernelWithArity6<I1, I2, I3, I4, I5, I6> {
     fn execute(
         &self,
         grid: Grid,
@@ -99,12 +100,13 @@ pub trait ModuleKernelWithArity5<I1, I2, I3, I4, I5> {
         i3: I3,
         i4: I4,
         i5: I5,
+        i6: I6,
     ) -> Result<(), driver::Error>;
 }
 
-impl<F, I1, I2, I3, I4, I5> ModuleKernelWithArity5<I1, I2, I3, I4, I5> for Kernel<F>
+impl<F, I1, I2, I3, I4, I5, I6> ModuleKernelWithArity6<I1, I2, I3, I4, I5, I6> for Kernel<F>
 where
-    F: KernelPlaceholder<Args = (I1, I2, I3, I4, I5)>,
+    F: KernelPlaceholder<Args = (I1, I2, I3, I4, I5, I6)>,
 {
     fn execute(
         &self,
@@ -115,9 +117,57 @@ where
         i3: I3,
         i4: I4,
         i5: I5,
+        i6: I6,
     ) -> Result<(), driver::Error> {
         self.handle.launch(
-            &[Any(&i1), Any(&i2), Any(&i3), Any(&i4), Any(&i5)],
+            &[Any(&i1), Any(&i2), Any(&i3), Any(&i4), Any(&i5), Any(&i6)],
+            grid,
+            block,
+        )
+    }
+}
+
+pub trait ModuleKernelWithArity7<I1, I2, I3, I4, I5, I6, I7> {
+    fn execute(
+        &self,
+        grid: Grid,
+        block: Block,
+        i1: I1,
+        i2: I2,
+        i3: I3,
+        i4: I4,
+        i5: I5,
+        i6: I6,
+        i7: I7,
+    ) -> Result<(), driver::Error>;
+}
+
+impl<F, I1, I2, I3, I4, I5, I6, I7> ModuleKernelWithArity7<I1, I2, I3, I4, I5, I6, I7> for Kernel<F>
+where
+    F: KernelPlaceholder<Args = (I1, I2, I3, I4, I5, I6, I7)>,
+{
+    fn execute(
+        &self,
+        grid: Grid,
+        block: Block,
+        i1: I1,
+        i2: I2,
+        i3: I3,
+        i4: I4,
+        i5: I5,
+        i6: I6,
+        i7: I7,
+    ) -> Result<(), driver::Error> {
+        self.handle.launch(
+            &[
+                Any(&i1),
+                Any(&i2),
+                Any(&i3),
+                Any(&i4),
+                Any(&i5),
+                Any(&i6),
+                Any(&i7),
+            ],
             grid,
             block,
         )
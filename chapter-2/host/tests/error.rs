@@ -0,0 +1,81 @@
+extern crate chapter_2;
+extern crate png;
+
+use std::error::Error;
+
+use png::ColorType;
+
+use chapter_2::image::{Format, Image, ImageError, Luma, Rgba};
+
+/// Stands for the error type of an application built on top of the crate.
+#[derive(Debug)]
+enum AppError {
+    Image(ImageError),
+}
+
+impl From<ImageError> for AppError {
+    fn from(error: ImageError) -> Self {
+        AppError::Image(error)
+    }
+}
+
+fn load(bytes: &[u8]) -> Result<Image<Luma>, AppError> {
+    Ok(Image::from_bytes(bytes)?)
+}
+
+fn describe<E: Error>(error: &E) -> String {
+    error.to_string()
+}
+
+#[test]
+fn should_report_truncated_png() {
+    let image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let bytes = image.to_bytes(Format::Png).unwrap();
+
+    match Image::<Luma>::from_bytes(&bytes[..bytes.len() / 2]) {
+        Err(ImageError::UnsupportedColorType(ColorType::RGB)) => {}
+        _ => panic!("RGB image must not be loaded as grayscale"),
+    }
+
+    let truncated: Result<Image, _> = Image::from_bytes(&bytes[..bytes.len() / 2]);
+
+    match truncated {
+        Err(ImageError::TruncatedData) => {}
+        _ => panic!("Truncated PNG must be rejected"),
+    }
+}
+
+#[test]
+fn should_report_dimension_overflow() {
+    let header = format!("P5\n{} {}\n255\n", usize::max_value() / 2, 3);
+
+    match Image::<Luma>::from_bytes(header.as_bytes()) {
+        Err(ImageError::DimensionOverflow) => {}
+        _ => panic!("Huge image must be rejected"),
+    }
+}
+
+#[test]
+fn should_report_saving_errors() {
+    let image = Image::<Rgba>::new(8, 8);
+
+    match image.to_bytes(Format::Ppm) {
+        Err(ImageError::UnsupportedColorType(ColorType::RGBA)) => {}
+        _ => panic!("Pixmap can't store alpha"),
+    }
+
+    match image.save("/nonexistent/chapter-2-error.png") {
+        Err(ImageError::Io(_)) => {}
+        _ => panic!("Missing directory must be reported"),
+    }
+}
+
+#[test]
+fn should_convert_into_application_errors() {
+    match load(b"GIF89a") {
+        Err(AppError::Image(ref error)) => {
+            assert_eq!(describe(error), "Unsupported file format");
+        }
+        _ => panic!("GIF must be rejected"),
+    }
+}
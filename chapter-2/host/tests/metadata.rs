@@ -0,0 +1,36 @@
+extern crate chapter_2;
+
+use std::env::temp_dir;
+
+use chapter_2::filter::bilateral_cuda as filter;
+use chapter_2::image::{Image, Metadata, PhysicalDimensions};
+
+fn metadata() -> Metadata {
+    Metadata {
+        gamma: Some(45455),
+        srgb_intent: Some(1),
+        icc_profile: None,
+        physical_dimensions: Some(PhysicalDimensions {
+            x_pixels_per_unit: 2835,
+            y_pixels_per_unit: 2835,
+            meter: true,
+        }),
+        text: vec![("Title".to_string(), "Bilateral".to_string())],
+    }
+}
+
+#[test]
+fn should_save_and_open_metadata() {
+    let mut image: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    image.metadata = metadata();
+
+    let output = filter(&image, 5, 3.5, 3.0).unwrap();
+
+    let path = temp_dir().join("chapter-2-metadata-512.png");
+    output.save(path.to_str().unwrap()).unwrap();
+
+    let reopened: Image = Image::open(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(output.metadata, metadata());
+    assert_eq!(reopened.metadata, metadata());
+}
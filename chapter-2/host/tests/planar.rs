@@ -0,0 +1,77 @@
+extern crate chapter_2;
+
+mod utils;
+use utils::compare_images;
+
+use chapter_2::filter::{bilateral_cuda_planar, bilateral_cuda_with, BorderMode, Options};
+use chapter_2::generate;
+use chapter_2::image::{Image, Pixel, Rgba};
+use chapter_2::planar::PlanarImage;
+
+#[test]
+fn should_convert_to_planes_and_back() {
+    let input: Image = Image::open("../../fixtures/input-512.png").unwrap();
+    let planar = PlanarImage::from_image(&input);
+
+    assert_eq!(planar.plane(1)[3], input.pixels[3].g);
+    assert!(planar.to_image::<Pixel>().pixels == input.pixels);
+}
+
+#[test]
+fn should_produce_correct_planar_image_512() {
+    let input: Image = Image::open("../../fixtures/input-512.png").unwrap();
+
+    let planar = PlanarImage::from_image(&input);
+
+    let current_output = bilateral_cuda_planar(&planar, 5, 3.5, 3.0, Options::default());
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    compare_images(&current_output.unwrap().to_image(), &reference_output);
+}
+
+#[test]
+fn should_honour_border_modes() {
+    let image: Image = generate::photographic(64, 48, 1);
+    let planar = PlanarImage::from_image(&image);
+
+    for &border in &[
+        BorderMode::Replicate,
+        BorderMode::Mirror,
+        BorderMode::Wrap,
+        BorderMode::Constant,
+    ] {
+        let options = Options {
+            border,
+            border_value: 200.0,
+            ..Options::default()
+        };
+
+        compare_images(
+            &bilateral_cuda_planar(&planar, 4, 3.5, 30.0, options)
+                .unwrap()
+                .to_image(),
+            &bilateral_cuda_with(&image, 4, 3.5, 30.0, options).unwrap(),
+        );
+    }
+}
+
+#[test]
+fn should_leave_alpha_out_of_range_distance() {
+    let colors: Image = generate::photographic(64, 48, 1);
+    let mut image = Image::<Rgba>::new(64, 48);
+
+    for (index, (target, pixel)) in image.pixels.iter_mut().zip(&colors.pixels).enumerate() {
+        *target = Rgba {
+            r: pixel.r,
+            g: pixel.g,
+            b: pixel.b,
+            a: (index * 37 % 256) as u8,
+        };
+    }
+
+    let planar = PlanarImage::from_image(&image);
+    let output = bilateral_cuda_planar(&planar, 4, 3.5, 30.0, Options::default()).unwrap();
+    let reference_output = bilateral_cuda_with(&image, 4, 3.5, 30.0, Options::default()).unwrap();
+
+    assert!(output.to_image::<Rgba>().pixels == reference_output.pixels);
+}
@@ -0,0 +1,107 @@
+extern crate chapter_2;
+
+mod utils;
+
+use std::f64;
+
+use chapter_2::filter::{bilateral_cuda_planar, bilateral_cuda_with, BorderMode, Options, WindowMode};
+use chapter_2::generate;
+use chapter_2::image::{Image, Luma};
+use chapter_2::planar::PlanarImage;
+use chapter_2::transform::{flip_horizontal, rotate180};
+
+use utils::compare_images;
+
+fn options(window: WindowMode) -> Options {
+    Options {
+        window,
+        border: BorderMode::Mirror,
+        ..Options::default()
+    }
+}
+
+// With infinite sigmas every weight is exactly 1, so the filter is a plain box average.
+fn box_filter(image: &Image<Luma<f32>>, window: WindowMode) -> Image<Luma<f32>> {
+    let options = Options {
+        window,
+        ..Options::default()
+    };
+
+    bilateral_cuda_with(image, 1, f64::INFINITY, f64::INFINITY, options).unwrap()
+}
+
+// Filtering a flipped image and flipping the result back only gives the same image when the
+// window is as wide on both sides of the center.
+fn reoriented<F: Fn(&Image) -> Image>(image: &Image, window: WindowMode, transform: F) -> Image {
+    let filtered = bilateral_cuda_with(&transform(image), 3, 2.0, 30.0, options(window));
+
+    transform(&filtered.unwrap())
+}
+
+#[test]
+fn should_keep_legacy_window_by_default() {
+    let input = Image::open("../../fixtures/input-512.png").unwrap();
+    let reference_output = Image::open("../../fixtures/ref-output-512.png").unwrap();
+
+    let options = Options {
+        window: WindowMode::Legacy,
+        ..Options::default()
+    };
+
+    compare_images(
+        &bilateral_cuda_with(&input, 5, 3.5, 3.0, options).unwrap(),
+        &reference_output,
+    );
+}
+
+#[test]
+fn should_cover_whole_window() {
+    let mut impulse = Image::<Luma<f32>>::new(16, 16);
+    impulse.pixels[8 * 16 + 8].y = 36.0;
+
+    let symmetric = box_filter(&impulse, WindowMode::Symmetric);
+    let legacy = box_filter(&impulse, WindowMode::Legacy);
+
+    for i in 0..16 {
+        for j in 0..16 {
+            let inside = |low: usize, high: usize| i >= low && i <= high && j >= low && j <= high;
+
+            let expected = if inside(7, 9) { 4.0 } else { 0.0 };
+            assert_eq!(symmetric.pixels[i * 16 + j].y, expected, "at {}, {}", i, j);
+
+            // Only 2 by 2 pixels, below and to the right of the impulse, see it.
+            let expected = if inside(8, 9) { 9.0 } else { 0.0 };
+            assert_eq!(legacy.pixels[i * 16 + j].y, expected, "at {}, {}", i, j);
+        }
+    }
+}
+
+#[test]
+fn should_not_depend_on_orientation() {
+    let image: Image = generate::photographic(64, 48, 5);
+
+    let filtered =
+        bilateral_cuda_with(&image, 3, 2.0, 30.0, options(WindowMode::Symmetric)).unwrap();
+
+    compare_images(
+        &reoriented(&image, WindowMode::Symmetric, flip_horizontal),
+        &filtered,
+    );
+    compare_images(
+        &reoriented(&image, WindowMode::Symmetric, rotate180),
+        &filtered,
+    );
+}
+
+#[test]
+fn should_cover_whole_window_of_planar_images() {
+    let image: Image = generate::photographic(64, 48, 7);
+    let options = options(WindowMode::Symmetric);
+
+    let planar = bilateral_cuda_planar(&PlanarImage::from_image(&image), 4, 3.5, 30.0, options);
+
+    compare_images(
+        &planar.unwrap().to_image(),
+        &bilateral_cuda_with(&image, 4, 3.5, 30.0, options).unwrap(),
+    );
+}